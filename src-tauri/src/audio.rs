@@ -1,9 +1,17 @@
+use crate::config::{AudioFormat, Config};
 use anyhow::{Context, Result};
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
-use cpal::{Device, Sample, SampleFormat};
+use cpal::{BufferSize, Device, FromSample, SampleFormat, SizedSample, SupportedBufferSize};
+use ringbuf::traits::{Consumer, Observer, Producer, Split};
+use ringbuf::{HeapCons, HeapProd, HeapRb};
+use rubato::{FftFixedIn, Resampler};
+use std::collections::VecDeque;
 use std::io::Cursor;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
 use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::Duration;
 
 #[derive(Clone)]
 pub struct AudioDevice {
@@ -34,168 +42,390 @@ pub fn get_default_device() -> Option<AudioDevice> {
     })
 }
 
-pub struct AudioRecorder {
-    samples: Arc<Mutex<Vec<f32>>>,
-    sample_rate: u32,
-    channels: u16,
-    is_recording: Arc<AtomicBool>,
-    stream: cpal::Stream,
+// Look up an input device by name, falling back to the system default
+pub fn find_input_device(name: Option<&str>) -> Option<AudioDevice> {
+    if let Some(name) = name {
+        if let Some(device) = list_input_devices().into_iter().find(|d| d.name == name) {
+            return Some(device);
+        }
+        tracing::warn!("audio device '{}' not found, using default", name);
+    }
+
+    get_default_device()
 }
 
-fn build_stream(
-    device: &Device,
-    samples: Arc<Mutex<Vec<f32>>>,
-    is_recording: Arc<AtomicBool>,
-) -> Result<(cpal::Stream, u32, u16)> {
-    let config = device
-        .default_input_config()
-        .context("failed to get default input config")?;
+pub fn input_device_available(name: &str) -> bool {
+    list_input_devices().iter().any(|d| d.name == name)
+}
 
-    let sample_rate = config.sample_rate().0;
-    let channels = config.channels();
-
-    let err_fn = |err| tracing::error!("audio stream error: {}", err);
-
-    let stream = match config.sample_format() {
-        SampleFormat::F32 => {
-            let samples_c = Arc::clone(&samples);
-            let is_rec_c = Arc::clone(&is_recording);
-            device.build_input_stream(
-                &config.into(),
-                move |data: &[f32], _| {
-                    if is_rec_c.load(Ordering::SeqCst) {
-                        let mut samples = samples_c.lock().unwrap();
-                        samples.extend_from_slice(data);
-                    }
-                },
-                err_fn,
-                None,
-            )?
-        }
-        SampleFormat::I16 => {
-            let samples_c = Arc::clone(&samples);
-            let is_rec_c = Arc::clone(&is_recording);
-            device.build_input_stream(
-                &config.into(),
-                move |data: &[i16], _| {
-                    if is_rec_c.load(Ordering::SeqCst) {
-                        let mut samples = samples_c.lock().unwrap();
-                        samples.extend(data.iter().map(|&s| s.to_sample::<f32>()));
-                    }
-                },
-                err_fn,
-                None,
-            )?
-        }
-        SampleFormat::U16 => {
-            let samples_c = Arc::clone(&samples);
-            let is_rec_c = Arc::clone(&is_recording);
-            device.build_input_stream(
-                &config.into(),
-                move |data: &[u16], _| {
-                    if is_rec_c.load(Ordering::SeqCst) {
-                        let mut samples = samples_c.lock().unwrap();
-                        samples.extend(data.iter().map(|&s| s.to_sample::<f32>()));
-                    }
-                },
-                err_fn,
-                None,
-            )?
+// Frames per chunk fed to the FFT resampler
+const RESAMPLE_CHUNK: usize = 1024;
+
+// Voice activity detection works on short frames of this length
+const VAD_FRAME_MS: u32 = 30;
+// Speech must be this much louder than the background noise
+const VAD_MARGIN_DB: f32 = 10.0;
+// Shorter bursts (trigger clicks, bumps) are not speech
+const VAD_MIN_SPEECH_FRAMES: usize = 3;
+// Context kept around detected speech so word edges aren't cut
+const VAD_PADDING_MS: u32 = 200;
+
+fn rms_db(samples: &[f32]) -> f32 {
+    if samples.is_empty() {
+        return f32::NEG_INFINITY;
+    }
+
+    let mean_square = samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32;
+    10.0 * mean_square.max(1e-10).log10()
+}
+
+fn frame_len(sample_rate: u32) -> usize {
+    (sample_rate * VAD_FRAME_MS / 1000).max(1) as usize
+}
+
+// How fast the live noise floor creeps up towards louder backgrounds, per frame
+const VAD_FLOOR_RISE_DB: f32 = 0.05;
+// The live detector sees raw, unnormalized input, so quiet mics sit well below the clip
+// threshold. Only digital silence and dither are ruled out; the noise floor does the rest.
+const VAD_LIVE_MIN_DB: f32 = -80.0;
+
+// When the live detector should report the end of speech
+#[derive(Debug, Clone, Copy)]
+pub struct SilenceSettings {
+    pub silence_ms: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SpeechChange {
+    Started,
+    Ended,
+}
+
+// Frame-by-frame speech detection on mono audio as it arrives
+struct SpeechDetector {
+    frame_len: usize,
+    frame: Vec<f32>,
+    noise_floor: Option<f32>,
+    speech_frames: usize,
+    silence_frames: usize,
+    in_speech: bool,
+}
+
+impl SpeechDetector {
+    fn new(sample_rate: u32) -> Self {
+        Self {
+            frame_len: frame_len(sample_rate),
+            frame: Vec::new(),
+            noise_floor: None,
+            speech_frames: 0,
+            silence_frames: 0,
+            in_speech: false,
         }
-        _ => anyhow::bail!("unsupported sample format"),
-    };
+    }
 
-    stream.play().context("failed to start audio stream")?;
+    // Forget the current utterance but keep the learned noise floor
+    fn reset(&mut self) {
+        self.speech_frames = 0;
+        self.silence_frames = 0;
+        self.in_speech = false;
+    }
+
+    fn push(&mut self, samples: &[f32], settings: &SilenceSettings) -> Option<SpeechChange> {
+        let mut change = None;
+
+        for &sample in samples {
+            self.frame.push(sample);
+            if self.frame.len() == self.frame_len {
+                let level = rms_db(&self.frame);
+                self.frame.clear();
+                change = self.process_frame(level, settings).or(change);
+            }
+        }
+
+        change
+    }
+
+    fn process_frame(&mut self, level: f32, settings: &SilenceSettings) -> Option<SpeechChange> {
+        // Drop straight to quieter frames, rise slowly so speech doesn't raise the floor
+        let floor = match self.noise_floor {
+            Some(floor) if level >= floor => floor + VAD_FLOOR_RISE_DB,
+            _ => level,
+        };
+        self.noise_floor = Some(floor);
+
+        let is_speech = level > VAD_LIVE_MIN_DB && level > floor + VAD_MARGIN_DB;
+        if is_speech {
+            self.speech_frames += 1;
+            self.silence_frames = 0;
+            if !self.in_speech && self.speech_frames >= VAD_MIN_SPEECH_FRAMES {
+                self.in_speech = true;
+                return Some(SpeechChange::Started);
+            }
+            return None;
+        }
+
+        self.speech_frames = 0;
+        if !self.in_speech {
+            return None;
+        }
+
+        self.silence_frames += 1;
+        if self.silence_frames as u32 * VAD_FRAME_MS >= settings.silence_ms {
+            self.reset();
+            return Some(SpeechChange::Ended);
+        }
 
-    Ok((stream, sample_rate, channels))
+        None
+    }
 }
 
-impl AudioRecorder {
-    pub fn new() -> Result<Self> {
-        let host = cpal::default_host();
-        let device = host
-            .default_input_device()
-            .context("no input device available")?;
+// Samples at or above this magnitude count as clipped
+const CLIP_LEVEL: f32 = 0.999;
+// Normalization never boosts more than this, so near-silence isn't blown up into noise
+const MAX_GAIN_DB: f32 = 30.0;
+// Soft limiter: untouched below the knee, smoothly approaching the ceiling (about -1 dBFS) above
+const LIMITER_KNEE: f32 = 0.7;
+const LIMITER_CEILING: f32 = 0.89;
 
-        let samples = Arc::new(Mutex::new(Vec::new()));
-        let is_recording = Arc::new(AtomicBool::new(false));
+// Overlap between consecutive chunks so a word cut at the boundary survives in one of them
+const CHUNK_OVERLAP_MS: u32 = 1000;
 
-        let (stream, sample_rate, channels) =
-            build_stream(&device, Arc::clone(&samples), Arc::clone(&is_recording))?;
+// Mono audio at a known sample rate, ready for processing and upload
+#[derive(Debug, Clone)]
+pub struct AudioClip {
+    pub samples: Vec<f32>,
+    pub sample_rate: u32,
+}
 
-        tracing::info!(
-            "audio stream ready: {} Hz, {} channels",
-            sample_rate,
-            channels
+impl AudioClip {
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    pub fn duration_secs(&self) -> f32 {
+        self.samples.len() as f32 / self.sample_rate as f32
+    }
+
+    // Fraction of samples at full scale, i.e. the input saturated
+    pub fn clipped_fraction(&self) -> f32 {
+        if self.samples.is_empty() {
+            return 0.0;
+        }
+
+        let clipped = self.samples.iter().filter(|s| s.abs() >= CLIP_LEVEL).count();
+        clipped as f32 / self.samples.len() as f32
+    }
+
+    // Bring the RMS level to `target_db` dBFS, then soft-limit the peaks
+    pub fn normalize(mut self, target_db: f32) -> Self {
+        let level = rms_db(&self.samples);
+        if !level.is_finite() {
+            return self;
+        }
+
+        let gain_db = (target_db - level).min(MAX_GAIN_DB);
+        let gain = 10f32.powf(gain_db / 20.0);
+        for sample in &mut self.samples {
+            *sample = limit(*sample * gain);
+        }
+
+        tracing::debug!("normalized from {:.1} dBFS ({:+.1} dB gain)", level, gain_db);
+
+        self
+    }
+
+    // Spectral gating against steady background noise (fans, air conditioning, office hum)
+    pub fn denoise(self, strength: f32) -> Result<Self> {
+        let start = std::time::Instant::now();
+        let samples = crate::denoise::spectral_gate(&self.samples, self.sample_rate, strength)?;
+        tracing::debug!(
+            "noise suppression on {:.1}s took {:?}",
+            self.duration_secs(),
+            start.elapsed()
         );
 
         Ok(Self {
             samples,
-            sample_rate,
-            channels,
-            is_recording,
-            stream,
+            sample_rate: self.sample_rate,
         })
     }
 
-    #[allow(dead_code)]
-    pub fn set_device(&mut self, device: &Device) -> Result<()> {
-        self.is_recording.store(false, Ordering::SeqCst);
+    // Cut non-speech from both ends; None when the clip contains no speech at all
+    pub fn trim_silence(self, threshold_db: f32) -> Option<Self> {
+        let frame_len = frame_len(self.sample_rate);
+        let levels: Vec<f32> = self.samples.chunks(frame_len).map(rms_db).collect();
+        if levels.is_empty() {
+            return None;
+        }
 
-        let (stream, sample_rate, channels) =
-            build_stream(device, Arc::clone(&self.samples), Arc::clone(&self.is_recording))?;
+        // Seed the noise floor from the quietest tenth of the recording
+        let mut sorted = levels.clone();
+        sorted.sort_by(|a, b| a.total_cmp(b));
+        let noise_floor = sorted[sorted.len() / 10];
 
-        self.stream = stream;
-        self.sample_rate = sample_rate;
-        self.channels = channels;
+        let speech: Vec<bool> = levels
+            .iter()
+            .map(|&level| level > threshold_db && level > noise_floor + VAD_MARGIN_DB)
+            .collect();
 
-        tracing::info!(
-            "switched audio device: {} Hz, {} channels",
-            sample_rate,
-            channels
+        // Find the first and last run of speech long enough to count
+        let mut first = None;
+        let mut last = None;
+        let mut run_start = 0;
+        for (i, &is_speech) in speech.iter().chain(std::iter::once(&false)).enumerate() {
+            if is_speech {
+                continue;
+            }
+            if i - run_start >= VAD_MIN_SPEECH_FRAMES {
+                first.get_or_insert(run_start);
+                last = Some(i);
+            }
+            run_start = i + 1;
+        }
+
+        let (Some(first), Some(last)) = (first, last) else {
+            tracing::info!(
+                "no speech detected in {:.1}s of audio",
+                self.duration_secs()
+            );
+            return None;
+        };
+
+        let padding = (self.sample_rate * VAD_PADDING_MS / 1000) as usize;
+        let start = (first * frame_len).saturating_sub(padding);
+        let end = (last * frame_len + padding).min(self.samples.len());
+
+        tracing::debug!(
+            "trimmed silence: kept {:.2}s-{:.2}s of {:.2}s",
+            start as f32 / self.sample_rate as f32,
+            end as f32 / self.sample_rate as f32,
+            self.duration_secs()
         );
 
-        Ok(())
+        Some(Self {
+            samples: self.samples[start..end].to_vec(),
+            sample_rate: self.sample_rate,
+        })
     }
 
-    pub fn start(&mut self) -> Result<()> {
-        if self.is_recording.load(Ordering::SeqCst) {
-            return Ok(());
+    // Split into pieces of at most `max_secs`, cutting at the quietest frame in the
+    // second half of each piece, or right at the limit when there is no pause to cut at;
+    // consecutive pieces overlap by CHUNK_OVERLAP_MS.
+    // Each piece comes with its start time in seconds.
+    pub fn split_at_silence(&self, max_secs: u32) -> Vec<(f32, Self)> {
+        let max_len = (max_secs as u64 * self.sample_rate as u64) as usize;
+        let overlap = (self.sample_rate * CHUNK_OVERLAP_MS / 1000) as usize;
+        let frame_len = frame_len(self.sample_rate);
+        if max_len < (overlap + frame_len) * 2 || self.samples.len() <= max_len {
+            return vec![(0.0, self.clone())];
         }
 
-        {
-            let mut samples = self.samples.lock().unwrap();
-            samples.clear();
+        let mut chunks = Vec::new();
+        let mut start = 0;
+        while self.samples.len() - start > max_len {
+            let window = start + max_len / 2..start + max_len - frame_len;
+            let piece_db = rms_db(&self.samples[start..start + max_len]);
+            let cut = window
+                .step_by(frame_len)
+                .map(|i| (i, rms_db(&self.samples[i..i + frame_len])))
+                .min_by(|a, b| a.1.total_cmp(&b.1))
+                // A dip that is still speech is no better a place to cut than the limit
+                .filter(|&(_, db)| db <= piece_db - VAD_MARGIN_DB)
+                .map_or(start + max_len, |(i, _)| i + frame_len / 2);
+
+            chunks.push((
+                start as f32 / self.sample_rate as f32,
+                Self {
+                    samples: self.samples[start..cut].to_vec(),
+                    sample_rate: self.sample_rate,
+                },
+            ));
+            start = cut - overlap;
         }
+        chunks.push((
+            start as f32 / self.sample_rate as f32,
+            Self {
+                samples: self.samples[start..].to_vec(),
+                sample_rate: self.sample_rate,
+            },
+        ));
 
-        self.is_recording.store(true, Ordering::SeqCst);
-        tracing::info!("recording started");
+        tracing::info!(
+            "split {:.1}s of audio into {} chunks",
+            self.duration_secs(),
+            chunks.len()
+        );
 
-        Ok(())
+        chunks
     }
 
-    pub fn stop(&mut self) -> Result<Vec<u8>> {
-        self.is_recording.store(false, Ordering::SeqCst);
+    pub fn resample(self, target_rate: u32) -> Result<Self> {
+        if target_rate == 0 || target_rate == self.sample_rate || self.samples.is_empty() {
+            return Ok(self);
+        }
 
-        let samples = {
-            let samples = self.samples.lock().unwrap();
-            samples.clone()
+        let samples = resample(&self.samples, self.sample_rate, target_rate)?;
+        tracing::debug!(
+            "resampled {} Hz -> {} Hz ({} -> {} samples)",
+            self.sample_rate,
+            target_rate,
+            self.samples.len(),
+            samples.len()
+        );
+
+        Ok(Self {
+            samples,
+            sample_rate: target_rate,
+        })
+    }
+
+    pub fn encode(&self, format: AudioFormat) -> Result<EncodedAudio> {
+        let start = std::time::Instant::now();
+        let encoded = match format {
+            AudioFormat::Wav => EncodedAudio {
+                data: self.encode_wav()?,
+                mime_type: "audio/wav",
+                file_name: "audio.wav",
+            },
+            AudioFormat::Flac => EncodedAudio {
+                data: self.encode_flac()?,
+                mime_type: "audio/flac",
+                file_name: "audio.flac",
+            },
+            #[cfg(feature = "opus")]
+            AudioFormat::Opus => EncodedAudio {
+                data: self.encode_opus()?,
+                mime_type: "audio/ogg",
+                file_name: "audio.ogg",
+            },
+            // Builds without libopus send FLAC instead, which every backend accepts
+            #[cfg(not(feature = "opus"))]
+            AudioFormat::Opus => {
+                tracing::warn!("opus support is not built in, uploading flac instead");
+                return self.encode(AudioFormat::Flac);
+            }
         };
 
-        if samples.is_empty() {
-            tracing::warn!("no audio samples recorded");
-            return Ok(Vec::new());
-        }
+        tracing::debug!(
+            "encoded {:.1}s of audio as {} ({} bytes) in {:?}",
+            self.duration_secs(),
+            encoded.mime_type,
+            encoded.data.len(),
+            start.elapsed()
+        );
 
-        tracing::info!("recording stopped: {} samples", samples.len());
+        Ok(encoded)
+    }
 
-        let wav_data = self.encode_wav(&samples)?;
-        Ok(wav_data)
+    fn pcm16(&self) -> impl Iterator<Item = i16> + '_ {
+        self.samples
+            .iter()
+            .map(|&s| (s.clamp(-1.0, 1.0) * i16::MAX as f32) as i16)
     }
 
-    fn encode_wav(&self, samples: &[f32]) -> Result<Vec<u8>> {
+    pub fn encode_wav(&self) -> Result<Vec<u8>> {
         let spec = hound::WavSpec {
-            channels: self.channels,
+            channels: 1,
             sample_rate: self.sample_rate,
             bits_per_sample: 16,
             sample_format: hound::SampleFormat::Int,
@@ -206,8 +436,7 @@ impl AudioRecorder {
             let mut writer =
                 hound::WavWriter::new(&mut cursor, spec).context("failed to create wav writer")?;
 
-            for &sample in samples {
-                let amplitude = (sample * i16::MAX as f32) as i16;
+            for amplitude in self.pcm16() {
                 writer
                     .write_sample(amplitude)
                     .context("failed to write sample")?;
@@ -218,4 +447,948 @@ impl AudioRecorder {
 
         Ok(cursor.into_inner())
     }
+
+    fn encode_flac(&self) -> Result<Vec<u8>> {
+        use flacenc::component::BitRepr;
+        use flacenc::error::Verify;
+
+        let samples: Vec<i32> = self.pcm16().map(i32::from).collect();
+        let config = flacenc::config::Encoder::default()
+            .into_verified()
+            .map_err(|(_, e)| anyhow::anyhow!("invalid flac encoder config: {:?}", e))?;
+        let source =
+            flacenc::source::MemSource::from_samples(&samples, 1, 16, self.sample_rate as usize);
+        let stream = flacenc::encode_with_fixed_block_size(&config, source, config.block_size)
+            .map_err(|e| anyhow::anyhow!("flac encoding failed: {:?}", e))?;
+
+        let mut sink = flacenc::bitsink::ByteSink::new();
+        stream
+            .write(&mut sink)
+            .map_err(|e| anyhow::anyhow!("failed to write flac stream: {:?}", e))?;
+
+        Ok(sink.into_inner())
+    }
+
+    #[cfg(feature = "opus")]
+    fn encode_opus(&self) -> Result<Vec<u8>> {
+        use audiopus::coder::Encoder;
+        use audiopus::{Application, Bitrate, Channels, SampleRate};
+        use ogg::writing::{PacketWriteEndInfo, PacketWriter};
+
+        // Opus only runs at a handful of rates
+        let clip = match SampleRate::try_from(self.sample_rate as i32) {
+            Ok(_) => self.clone(),
+            Err(_) => self.clone().resample(OPUS_FALLBACK_RATE)?,
+        };
+        let sample_rate = SampleRate::try_from(clip.sample_rate as i32)
+            .map_err(|e| anyhow::anyhow!("unsupported opus sample rate: {}", e))?;
+
+        let mut encoder = Encoder::new(sample_rate, Channels::Mono, Application::Voip)
+            .map_err(|e| anyhow::anyhow!("failed to create opus encoder: {}", e))?;
+        encoder
+            .set_bitrate(Bitrate::BitsPerSecond(OPUS_BITRATE))
+            .map_err(|e| anyhow::anyhow!("failed to set opus bitrate: {}", e))?;
+
+        // Granule positions are always counted at 48 kHz
+        let scale = 48_000 / clip.sample_rate as u64;
+        let pre_skip = encoder.lookahead().unwrap_or(0) as u64 * scale;
+
+        let mut writer = PacketWriter::new(Vec::new());
+        writer.write_packet(
+            opus_head(pre_skip as u16, clip.sample_rate),
+            OPUS_SERIAL,
+            PacketWriteEndInfo::EndPage,
+            0,
+        )?;
+        writer.write_packet(opus_tags(), OPUS_SERIAL, PacketWriteEndInfo::EndPage, 0)?;
+
+        let frame_len = (clip.sample_rate * OPUS_FRAME_MS / 1000) as usize;
+        let frames: Vec<&[f32]> = clip.samples.chunks(frame_len).collect();
+        let mut frame = vec![0.0f32; frame_len];
+        let mut packet = vec![0u8; OPUS_MAX_PACKET];
+        let mut position = 0u64;
+
+        for (i, chunk) in frames.iter().enumerate() {
+            // Zero-pad the last frame; the granule position tells decoders where audio ends
+            frame[..chunk.len()].copy_from_slice(chunk);
+            frame[chunk.len()..].fill(0.0);
+
+            let len = encoder
+                .encode_float(&frame, &mut packet)
+                .map_err(|e| anyhow::anyhow!("opus encoding failed: {}", e))?;
+
+            position += chunk.len() as u64 * scale;
+            let end = if i + 1 == frames.len() {
+                PacketWriteEndInfo::EndStream
+            } else {
+                PacketWriteEndInfo::NormalPacket
+            };
+            writer.write_packet(
+                packet[..len].to_vec(),
+                OPUS_SERIAL,
+                end,
+                pre_skip + position,
+            )?;
+        }
+
+        Ok(writer.into_inner())
+    }
+}
+
+// Compressed or raw audio plus what the multipart upload needs to describe it
+pub struct EncodedAudio {
+    pub data: Vec<u8>,
+    pub mime_type: &'static str,
+    pub file_name: &'static str,
+}
+
+// Opus packets cover 20 ms of audio
+#[cfg(feature = "opus")]
+const OPUS_FRAME_MS: u32 = 20;
+// Plenty for intelligible speech, about a tenth of 16 kHz WAV
+#[cfg(feature = "opus")]
+const OPUS_BITRATE: i32 = 24_000;
+#[cfg(feature = "opus")]
+const OPUS_MAX_PACKET: usize = 4000;
+#[cfg(feature = "opus")]
+const OPUS_FALLBACK_RATE: u32 = 48_000;
+#[cfg(feature = "opus")]
+const OPUS_SERIAL: u32 = 1;
+
+// Identification header (RFC 7845, section 5.1)
+#[cfg(feature = "opus")]
+fn opus_head(pre_skip: u16, input_sample_rate: u32) -> Vec<u8> {
+    let mut head = Vec::with_capacity(19);
+    head.extend_from_slice(b"OpusHead");
+    head.push(1); // version
+    head.push(1); // channels
+    head.extend_from_slice(&pre_skip.to_le_bytes());
+    head.extend_from_slice(&input_sample_rate.to_le_bytes());
+    head.extend_from_slice(&0i16.to_le_bytes()); // output gain
+    head.push(0); // mono/stereo channel mapping
+    head
+}
+
+// Comment header (RFC 7845, section 5.2) with no user comments
+#[cfg(feature = "opus")]
+fn opus_tags() -> Vec<u8> {
+    let vendor = b"ezwhisper";
+    let mut tags = Vec::with_capacity(16 + vendor.len());
+    tags.extend_from_slice(b"OpusTags");
+    tags.extend_from_slice(&(vendor.len() as u32).to_le_bytes());
+    tags.extend_from_slice(vendor);
+    tags.extend_from_slice(&0u32.to_le_bytes());
+    tags
+}
+
+fn limit(sample: f32) -> f32 {
+    let magnitude = sample.abs();
+    if magnitude <= LIMITER_KNEE {
+        return sample;
+    }
+
+    let headroom = LIMITER_CEILING - LIMITER_KNEE;
+    let limited = LIMITER_KNEE + headroom * ((magnitude - LIMITER_KNEE) / headroom).tanh();
+    limited.copysign(sample)
+}
+
+// Average interleaved channels into a single mono channel
+pub fn downmix(samples: &[f32], channels: u16, selection: &[usize]) -> Vec<f32> {
+    if channels <= 1 {
+        return samples.to_vec();
+    }
+
+    // Only the selected channels (0-based), or all of them when nothing is selected
+    samples
+        .chunks_exact(channels as usize)
+        .map(|frame| {
+            if selection.is_empty() {
+                frame.iter().sum::<f32>() / channels as f32
+            } else {
+                selection.iter().map(|&c| frame[c]).sum::<f32>() / selection.len() as f32
+            }
+        })
+        .collect()
+}
+
+// Validate 1-based channel numbers from the config against the device's channel count
+fn select_channels(requested: &[u16], channels: u16) -> Vec<usize> {
+    let selection: Vec<usize> = requested
+        .iter()
+        .filter(|&&c| c >= 1 && c <= channels)
+        .map(|&c| c as usize - 1)
+        .collect();
+
+    if selection.len() < requested.len() {
+        tracing::warn!(
+            "ignoring channels not present on a {}-channel device: {:?}",
+            channels,
+            requested
+        );
+    }
+
+    selection
+}
+
+// Band-limited FFT resampling of a mono signal
+pub fn resample(samples: &[f32], from: u32, to: u32) -> Result<Vec<f32>> {
+    if from == to || samples.is_empty() {
+        return Ok(samples.to_vec());
+    }
+
+    let mut resampler = StreamResampler::new(from, to)?;
+    let mut output = resampler.process(samples)?;
+    output.extend(resampler.flush()?);
+    Ok(output)
+}
+
+// Resamples audio that arrives in pieces, carrying leftover input and filter state between calls
+pub struct StreamResampler {
+    resampler: Option<FftFixedIn<f32>>,
+    from: u32,
+    to: u32,
+    pending: Vec<f32>,
+    // Leading output frames that are only filter delay
+    delay: usize,
+    input_frames: u64,
+    output_frames: u64,
+}
+
+impl StreamResampler {
+    pub fn new(from: u32, to: u32) -> Result<Self> {
+        let resampler = if from == to {
+            None
+        } else {
+            Some(
+                FftFixedIn::<f32>::new(from as usize, to as usize, RESAMPLE_CHUNK, 2, 1)
+                    .context("failed to create resampler")?,
+            )
+        };
+        let delay = resampler.as_ref().map_or(0, |r| r.output_delay());
+
+        Ok(Self {
+            resampler,
+            from,
+            to,
+            pending: Vec::new(),
+            delay,
+            input_frames: 0,
+            output_frames: 0,
+        })
+    }
+
+    // Resample whatever whole chunks are available, holding back the remainder for next time
+    pub fn process(&mut self, samples: &[f32]) -> Result<Vec<f32>> {
+        let Some(resampler) = self.resampler.as_mut() else {
+            return Ok(samples.to_vec());
+        };
+        self.pending.extend_from_slice(samples);
+        self.input_frames += samples.len() as u64;
+
+        let mut output = Vec::new();
+        let mut pos = 0;
+        while pos + resampler.input_frames_next() <= self.pending.len() {
+            let frames = resampler.input_frames_next();
+            let chunk = resampler
+                .process(&[&self.pending[pos..pos + frames]], None)
+                .context("resampling failed")?;
+            output.extend_from_slice(&chunk[0]);
+            pos += frames;
+        }
+        self.pending.drain(..pos);
+
+        Ok(self.emit(output))
+    }
+
+    // Resample the held-back input and drain the filter delay; call once at the end of the stream
+    pub fn flush(&mut self) -> Result<Vec<f32>> {
+        let Some(resampler) = self.resampler.as_mut() else {
+            return Ok(Vec::new());
+        };
+
+        let mut output = Vec::new();
+        if !self.pending.is_empty() {
+            let chunk = resampler
+                .process_partial(Some(&[&self.pending[..]]), None)
+                .context("resampling failed")?;
+            output.extend_from_slice(&chunk[0]);
+            self.pending.clear();
+        }
+
+        // Exactly as many frames as the input length implies, however much the filter pads
+        let expected = self.input_frames * self.to as u64 / self.from as u64;
+        let remaining = expected.saturating_sub(self.output_frames) as usize;
+        while output.len() < remaining + self.delay {
+            let chunk = resampler
+                .process_partial(None::<&[&[f32]]>, None)
+                .context("resampling failed")?;
+            if chunk[0].is_empty() {
+                break;
+            }
+            output.extend_from_slice(&chunk[0]);
+        }
+
+        output.truncate(remaining + self.delay);
+        Ok(self.emit(output))
+    }
+
+    // Drop the leading filter delay and count what is handed out
+    fn emit(&mut self, mut output: Vec<f32>) -> Vec<f32> {
+        let skip = self.delay.min(output.len());
+        self.delay -= skip;
+        output.drain(..skip);
+        self.output_frames += output.len() as u64;
+        output
+    }
+}
+
+// Seconds of audio the lock-free ring holds before the drain thread falls behind
+const RING_SECONDS: usize = 2;
+// How long the drain thread sleeps when the ring is empty
+const DRAIN_INTERVAL: Duration = Duration::from_millis(5);
+// Most samples moved out of the ring per drain pass
+const DRAIN_CHUNK: usize = 4096;
+
+// Window the level meter averages over, which also throttles level events
+const LEVEL_WINDOW_MS: u32 = 50;
+
+// Input level over one metering window, in dBFS
+#[derive(Debug, Clone, Copy, serde::Serialize)]
+pub struct AudioLevel {
+    pub rms_db: f32,
+    pub peak_db: f32,
+}
+
+// Sent from the drain thread while recording
+#[derive(Debug, Clone, Copy)]
+pub enum AudioEvent {
+    Level(AudioLevel),
+    SpeechStarted,
+    // Speech was followed by the configured stretch of silence
+    SpeechEnded,
+}
+
+struct LevelMeter {
+    window: usize,
+    sum_squares: f32,
+    peak: f32,
+    count: usize,
+}
+
+impl LevelMeter {
+    fn new(sample_rate: u32) -> Self {
+        Self {
+            window: (sample_rate * LEVEL_WINDOW_MS / 1000).max(1) as usize,
+            sum_squares: 0.0,
+            peak: 0.0,
+            count: 0,
+        }
+    }
+
+    fn reset(&mut self) {
+        self.sum_squares = 0.0;
+        self.peak = 0.0;
+        self.count = 0;
+    }
+
+    // Returns a level once a full window has been seen
+    fn push(&mut self, samples: &[f32]) -> Option<AudioLevel> {
+        for &s in samples {
+            self.sum_squares += s * s;
+            self.peak = self.peak.max(s.abs());
+        }
+        self.count += samples.len();
+
+        if self.count < self.window {
+            return None;
+        }
+
+        let mean_square = self.sum_squares / self.count as f32;
+        let level = AudioLevel {
+            rms_db: 10.0 * mean_square.max(1e-10).log10(),
+            peak_db: 20.0 * self.peak.max(1e-5).log10(),
+        };
+        self.reset();
+        Some(level)
+    }
+}
+
+// Audio collected by the drain thread, shared with the recorder
+struct CaptureBuffer {
+    samples: Vec<f32>,
+    // Rolling window of the most recent audio while idle, prepended on start
+    preroll: VecDeque<f32>,
+    preroll_capacity: usize,
+}
+
+impl CaptureBuffer {
+    fn new() -> Self {
+        Self {
+            samples: Vec::new(),
+            preroll: VecDeque::new(),
+            preroll_capacity: 0,
+        }
+    }
+
+    fn push(&mut self, recording: bool, data: &[f32]) {
+        if recording {
+            self.samples.extend_from_slice(data);
+            return;
+        }
+
+        self.preroll.extend(data);
+        let excess = self.preroll.len().saturating_sub(self.preroll_capacity);
+        self.preroll.drain(..excess);
+    }
+}
+
+// A running input stream and the thread draining its ring buffer
+struct Capture {
+    _stream: cpal::Stream,
+    running: Arc<AtomicBool>,
+    // Set by the stream error callback once the device has gone away
+    failed: Arc<AtomicBool>,
+    drain_thread: Option<JoinHandle<()>>,
+    sample_rate: u32,
+    channels: u16,
+    // 0-based channels mixed into the recording; empty mixes all of them
+    selection: Vec<usize>,
+}
+
+impl Drop for Capture {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::SeqCst);
+        if let Some(thread) = self.drain_thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+pub struct AudioRecorder {
+    buffer: Arc<Mutex<CaptureBuffer>>,
+    device_name: String,
+    // How many samples have already been handed out by read_new_samples
+    read_offset: usize,
+    preroll_ms: u32,
+    // Pre-roll set aside by hold_preroll, recorded by the next start()
+    held_preroll: Option<Vec<f32>>,
+    is_recording: Arc<AtomicBool>,
+    capture: Capture,
+    silence: Arc<Mutex<Option<SilenceSettings>>>,
+    events_rx: Receiver<AudioEvent>,
+}
+
+// Everything the real-time callbacks take ownership of
+struct StreamCallbacks {
+    producer: HeapProd<f32>,
+    overruns: Arc<AtomicUsize>,
+    failed: Arc<AtomicBool>,
+}
+
+// Real-time callback: wait-free push into the ring, no locks or allocation
+fn build_input_stream<T>(
+    device: &Device,
+    config: &cpal::StreamConfig,
+    callbacks: StreamCallbacks,
+) -> Result<cpal::Stream>
+where
+    T: SizedSample,
+    f32: FromSample<T>,
+{
+    let StreamCallbacks {
+        mut producer,
+        overruns,
+        failed,
+    } = callbacks;
+
+    let err_fn = move |err: cpal::StreamError| {
+        tracing::error!("audio stream error: {}", err);
+        if matches!(err, cpal::StreamError::DeviceNotAvailable) {
+            failed.store(true, Ordering::SeqCst);
+        }
+    };
+
+    let stream = device.build_input_stream(
+        config,
+        move |data: &[T], _| {
+            // Whole callbacks only, so frames never get split across channels
+            if producer.vacant_len() < data.len() {
+                overruns.fetch_add(data.len(), Ordering::Relaxed);
+                return;
+            }
+            producer.push_iter(data.iter().map(|&s| s.to_sample::<f32>()));
+        },
+        err_fn,
+        None,
+    )?;
+
+    Ok(stream)
+}
+
+// Requested input stream parameters; None keeps the device default
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StreamSettings {
+    pub sample_rate: Option<u32>,
+    pub channels: Option<u16>,
+    pub buffer_frames: Option<u32>,
+    // 1-based input channels to mix into the recording; empty mixes all of them
+    pub channel_selection: Vec<u16>,
+}
+
+impl From<&Config> for StreamSettings {
+    fn from(config: &Config) -> Self {
+        Self {
+            sample_rate: config.input_sample_rate,
+            channels: config.input_channels,
+            buffer_frames: config.input_buffer_frames,
+            channel_selection: config.channel_selection.clone(),
+        }
+    }
+}
+
+// Pick a supported input config matching the requested settings, else the device default
+fn choose_stream_config(
+    device: &Device,
+    settings: &StreamSettings,
+) -> Result<(cpal::StreamConfig, SampleFormat)> {
+    let default = device
+        .default_input_config()
+        .context("failed to get default input config")?;
+
+    let rate = settings.sample_rate.unwrap_or(default.sample_rate().0);
+    let channels = settings.channels.unwrap_or(default.channels());
+
+    let supported = if rate == default.sample_rate().0 && channels == default.channels() {
+        default
+    } else {
+        let found = device.supported_input_configs().ok().and_then(|configs| {
+            configs
+                .filter(|c| {
+                    c.channels() == channels
+                        && c.min_sample_rate().0 <= rate
+                        && rate <= c.max_sample_rate().0
+                })
+                // Prefer the format the device defaults to, then float formats
+                .min_by_key(|c| {
+                    (
+                        c.sample_format() != default.sample_format(),
+                        !c.sample_format().is_float(),
+                    )
+                })
+        });
+
+        match found {
+            Some(range) => range.with_sample_rate(cpal::SampleRate(rate)),
+            None => {
+                tracing::warn!(
+                    "input config {} Hz / {} channels not supported, using default",
+                    rate,
+                    channels
+                );
+                default
+            }
+        }
+    };
+
+    let sample_format = supported.sample_format();
+    let mut config = supported.config();
+    if let Some(frames) = settings.buffer_frames {
+        config.buffer_size = match supported.buffer_size() {
+            SupportedBufferSize::Range { min, max } => BufferSize::Fixed(frames.clamp(*min, *max)),
+            SupportedBufferSize::Unknown => BufferSize::Fixed(frames),
+        };
+    }
+
+    tracing::debug!(
+        "input stream config: {} Hz, {} channels, {}, buffer {:?}",
+        config.sample_rate.0,
+        config.channels,
+        sample_format,
+        config.buffer_size
+    );
+
+    Ok((config, sample_format))
+}
+
+fn build_capture(
+    device: &Device,
+    settings: &StreamSettings,
+    buffer: Arc<Mutex<CaptureBuffer>>,
+    is_recording: Arc<AtomicBool>,
+    silence: Arc<Mutex<Option<SilenceSettings>>>,
+    events: Sender<AudioEvent>,
+) -> Result<Capture> {
+    let (stream_config, sample_format) = choose_stream_config(device, settings)?;
+    let sample_rate = stream_config.sample_rate.0;
+    let channels = stream_config.channels;
+    let selection = select_channels(&settings.channel_selection, channels);
+
+    let ring = HeapRb::<f32>::new(sample_rate as usize * channels as usize * RING_SECONDS);
+    let (producer, consumer) = ring.split();
+    let overruns = Arc::new(AtomicUsize::new(0));
+    let failed = Arc::new(AtomicBool::new(false));
+
+    let callbacks = StreamCallbacks {
+        producer,
+        overruns: Arc::clone(&overruns),
+        failed: Arc::clone(&failed),
+    };
+
+    // Pro interfaces and ALSA devices often only offer 24/32-bit integer input
+    let stream = match sample_format {
+        SampleFormat::I8 => build_input_stream::<i8>(device, &stream_config, callbacks)?,
+        SampleFormat::I16 => build_input_stream::<i16>(device, &stream_config, callbacks)?,
+        SampleFormat::I32 => build_input_stream::<i32>(device, &stream_config, callbacks)?,
+        SampleFormat::I64 => build_input_stream::<i64>(device, &stream_config, callbacks)?,
+        SampleFormat::U8 => build_input_stream::<u8>(device, &stream_config, callbacks)?,
+        SampleFormat::U16 => build_input_stream::<u16>(device, &stream_config, callbacks)?,
+        SampleFormat::U32 => build_input_stream::<u32>(device, &stream_config, callbacks)?,
+        SampleFormat::U64 => build_input_stream::<u64>(device, &stream_config, callbacks)?,
+        SampleFormat::F32 => build_input_stream::<f32>(device, &stream_config, callbacks)?,
+        SampleFormat::F64 => build_input_stream::<f64>(device, &stream_config, callbacks)?,
+        other => anyhow::bail!("unsupported sample format: {}", other),
+    };
+
+    stream.play().context("failed to start audio stream")?;
+
+    let running = Arc::new(AtomicBool::new(true));
+    let drain = Drain {
+        consumer,
+        buffer,
+        is_recording,
+        overruns,
+        running: Arc::clone(&running),
+        channels,
+        selection: selection.clone(),
+        meter: LevelMeter::new(sample_rate),
+        detector: SpeechDetector::new(sample_rate),
+        silence,
+        events,
+    };
+    let drain_thread = std::thread::Builder::new()
+        .name("audio-drain".to_string())
+        .spawn(move || drain.run())
+        .context("failed to spawn audio drain thread")?;
+
+    Ok(Capture {
+        _stream: stream,
+        running,
+        failed,
+        drain_thread: Some(drain_thread),
+        sample_rate,
+        channels,
+        selection,
+    })
+}
+
+// Moves audio out of the ring into the capture buffer, off the real-time thread
+struct Drain {
+    consumer: HeapCons<f32>,
+    buffer: Arc<Mutex<CaptureBuffer>>,
+    is_recording: Arc<AtomicBool>,
+    overruns: Arc<AtomicUsize>,
+    running: Arc<AtomicBool>,
+    channels: u16,
+    selection: Vec<usize>,
+    meter: LevelMeter,
+    detector: SpeechDetector,
+    silence: Arc<Mutex<Option<SilenceSettings>>>,
+    events: Sender<AudioEvent>,
+}
+
+impl Drain {
+    fn run(mut self) {
+        let channels = self.channels.max(1) as usize;
+        let mut scratch = vec![0.0f32; DRAIN_CHUNK - DRAIN_CHUNK % channels];
+
+        while self.running.load(Ordering::SeqCst) {
+            let dropped = self.overruns.swap(0, Ordering::Relaxed);
+            if dropped > 0 {
+                tracing::warn!("audio ring buffer overrun, dropped {} samples", dropped);
+            }
+
+            // Keep whole frames together so the pre-roll stays channel-aligned
+            let available = self.consumer.occupied_len().min(scratch.len());
+            let count = self
+                .consumer
+                .pop_slice(&mut scratch[..available - available % channels]);
+            if count == 0 {
+                std::thread::sleep(DRAIN_INTERVAL);
+                continue;
+            }
+
+            let recording = {
+                let mut buffer = self.buffer.lock().unwrap();
+                let recording = self.is_recording.load(Ordering::SeqCst);
+                buffer.push(recording, &scratch[..count]);
+                recording
+            };
+
+            // Meter and detector hear the same channels as the transcriber
+            let mono = downmix(&scratch[..count], self.channels, &self.selection);
+
+            // Track the noise floor while idle too, so speech right after start is caught
+            let silence = *self.silence.lock().unwrap();
+            let change = silence.and_then(|settings| self.detector.push(&mono, &settings));
+
+            if !recording {
+                self.meter.reset();
+                self.detector.reset();
+                continue;
+            }
+
+            if let Some(level) = self.meter.push(&mono) {
+                let _ = self.events.send(AudioEvent::Level(level));
+            }
+
+            match change {
+                Some(SpeechChange::Started) => {
+                    let _ = self.events.send(AudioEvent::SpeechStarted);
+                }
+                Some(SpeechChange::Ended) => {
+                    let _ = self.events.send(AudioEvent::SpeechEnded);
+                }
+                None => {}
+            }
+        }
+    }
+}
+
+impl AudioRecorder {
+    pub fn new(
+        device_name: Option<&str>,
+        settings: StreamSettings,
+        preroll_ms: u32,
+    ) -> Result<Self> {
+        let device = find_input_device(device_name).context("no input device available")?;
+
+        let buffer = Arc::new(Mutex::new(CaptureBuffer::new()));
+        let is_recording = Arc::new(AtomicBool::new(false));
+        let silence = Arc::new(Mutex::new(None));
+        let (events_tx, events_rx) = mpsc::channel();
+
+        let capture = build_capture(
+            &device.device,
+            &settings,
+            Arc::clone(&buffer),
+            Arc::clone(&is_recording),
+            Arc::clone(&silence),
+            events_tx,
+        )?;
+
+        tracing::info!(
+            "audio stream ready on {}: {} Hz, {} channels",
+            device.name,
+            capture.sample_rate,
+            capture.channels
+        );
+
+        let mut recorder = Self {
+            buffer,
+            device_name: device.name,
+            read_offset: 0,
+            preroll_ms,
+            held_preroll: None,
+            is_recording,
+            capture,
+            silence,
+            events_rx,
+        };
+        recorder.set_preroll_ms(preroll_ms);
+
+        Ok(recorder)
+    }
+
+    pub fn set_preroll_ms(&mut self, preroll_ms: u32) {
+        self.preroll_ms = preroll_ms;
+
+        // Whole frames only, so the pre-roll always starts on the first channel
+        let frames = (self.capture.sample_rate as u64 * preroll_ms as u64 / 1000) as usize;
+        let mut buffer = self.buffer.lock().unwrap();
+        buffer.preroll_capacity = frames * self.capture.channels as usize;
+        let excess = buffer.preroll.len().saturating_sub(buffer.preroll_capacity);
+        buffer.preroll.drain(..excess);
+    }
+
+    pub fn device_name(&self) -> &str {
+        &self.device_name
+    }
+
+    // Report SpeechEnded after this much silence; None turns detection off
+    pub fn set_silence_detection(&mut self, settings: Option<SilenceSettings>) {
+        *self.silence.lock().unwrap() = settings;
+    }
+
+    pub fn try_recv(&self) -> Option<AudioEvent> {
+        self.events_rx.try_recv().ok()
+    }
+
+    // Whether the stream died, e.g. because the device was unplugged
+    pub fn is_failed(&self) -> bool {
+        self.capture.failed.load(Ordering::SeqCst)
+    }
+
+    // Set aside the pre-roll captured so far; start() then records it in place of whatever
+    // the microphone hears in between, such as the start cue
+    pub fn hold_preroll(&mut self) {
+        let mut buffer = self.buffer.lock().unwrap();
+        self.held_preroll = Some(buffer.preroll.drain(..).collect());
+    }
+
+    pub fn start(&mut self) -> Result<()> {
+        if self.is_recording.load(Ordering::SeqCst) {
+            return Ok(());
+        }
+
+        let held = self.held_preroll.take();
+        let preroll = {
+            let mut buffer = self.buffer.lock().unwrap();
+            let CaptureBuffer {
+                samples, preroll, ..
+            } = &mut *buffer;
+            samples.clear();
+            match held {
+                Some(held) => {
+                    preroll.clear();
+                    samples.extend(held);
+                }
+                None => samples.extend(preroll.drain(..)),
+            }
+
+            // Flip under the lock so no drained audio lands between pre-roll and recording
+            self.is_recording.store(true, Ordering::SeqCst);
+            samples.len()
+        };
+        self.read_offset = 0;
+
+        tracing::info!(
+            "recording started ({} ms pre-roll)",
+            preroll as u64 * 1000 / (self.sample_rate() as u64 * self.channels() as u64)
+        );
+
+        Ok(())
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.capture.sample_rate
+    }
+
+    pub fn channels(&self) -> u16 {
+        self.capture.channels
+    }
+
+    // Mono samples captured since the previous call, for streaming while recording
+    pub fn read_new_samples(&mut self) -> Vec<f32> {
+        let buffer = self.buffer.lock().unwrap();
+        let start = self.read_offset.min(buffer.samples.len());
+        self.read_offset = buffer.samples.len();
+        self.downmix(&buffer.samples[start..])
+    }
+
+    fn downmix(&self, samples: &[f32]) -> Vec<f32> {
+        downmix(samples, self.capture.channels, &self.capture.selection)
+    }
+
+    // Hand out everything captured so far, downmixed to mono, while recording continues
+    pub fn take_clip(&mut self) -> AudioClip {
+        let samples = std::mem::take(&mut self.buffer.lock().unwrap().samples);
+        self.read_offset = 0;
+
+        AudioClip {
+            samples: self.downmix(&samples),
+            sample_rate: self.sample_rate(),
+        }
+    }
+
+    // Stop capturing and return the recording downmixed to mono
+    pub fn stop(&mut self) -> Result<AudioClip> {
+        let samples = {
+            let mut buffer = self.buffer.lock().unwrap();
+            self.is_recording.store(false, Ordering::SeqCst);
+            std::mem::take(&mut buffer.samples)
+        };
+
+        if samples.is_empty() {
+            tracing::warn!("no audio samples recorded");
+        } else {
+            tracing::info!("recording stopped: {} samples", samples.len());
+        }
+
+        Ok(AudioClip {
+            samples: self.downmix(&samples),
+            sample_rate: self.sample_rate(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RATE: u32 = 1000;
+
+    // Steady signal with no pause anywhere
+    fn tone(secs: usize) -> Vec<f32> {
+        [0.5, -0.5].repeat(secs * RATE as usize / 2)
+    }
+
+    fn clip(samples: Vec<f32>) -> AudioClip {
+        AudioClip {
+            samples,
+            sample_rate: RATE,
+        }
+    }
+
+    #[test]
+    fn split_keeps_short_clip_whole() {
+        let chunks = clip(tone(3)).split_at_silence(5);
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0].0, 0.0);
+        assert_eq!(chunks[0].1.samples.len(), 3000);
+    }
+
+    #[test]
+    fn split_cuts_at_limit_without_silence() {
+        let chunks = clip(tone(7)).split_at_silence(3);
+
+        let spans: Vec<(f32, usize)> = chunks
+            .iter()
+            .map(|(offset, chunk)| (*offset, chunk.samples.len()))
+            .collect();
+        // Hard cuts every 3 s, each piece starting one overlap before the previous cut
+        assert_eq!(spans, vec![(0.0, 3000), (2.0, 3000), (4.0, 3000)]);
+    }
+
+    #[test]
+    fn split_cuts_in_pause() {
+        // Speech with a pause from 2.0 s to 2.3 s
+        let mut samples = tone(2);
+        samples.extend(vec![0.0; 300]);
+        samples.extend(tone(4));
+        let chunks = clip(samples).split_at_silence(3);
+
+        let cut = chunks[0].1.samples.len();
+        assert!((2000..=2300).contains(&cut), "cut at {}", cut);
+        // The next piece repeats the last CHUNK_OVERLAP_MS before the cut
+        let overlap = (RATE * CHUNK_OVERLAP_MS / 1000) as usize;
+        assert_eq!(chunks[1].0, (cut - overlap) as f32 / RATE as f32);
+
+        let end = chunks
+            .last()
+            .map(|(offset, chunk)| (offset * RATE as f32).round() as usize + chunk.samples.len());
+        assert_eq!(end, Some(6300));
+    }
+
+    #[test]
+    fn live_detector_hears_quiet_mic() {
+        // Raw input from a quiet mic: -75 dBFS background, speech around -60 dBFS
+        let background = |secs: f32| [0.000178, -0.000178].repeat((secs * RATE as f32) as usize / 2);
+        let speech = |secs: f32| [0.001, -0.001].repeat((secs * RATE as f32) as usize / 2);
+        let settings = SilenceSettings { silence_ms: 300 };
+        let mut detector = SpeechDetector::new(RATE);
+
+        assert_eq!(detector.push(&background(1.0), &settings), None);
+        assert_eq!(detector.push(&speech(0.5), &settings), Some(SpeechChange::Started));
+        assert_eq!(detector.push(&background(0.5), &settings), Some(SpeechChange::Ended));
+    }
 }
@@ -17,7 +17,7 @@ struct Message {
 struct ClaudeRequest {
     model: &'static str,
     max_tokens: u32,
-    system: &'static str,
+    system: String,
     messages: Vec<Message>,
 }
 
@@ -52,7 +52,7 @@ impl TextCleaner {
         let request = ClaudeRequest {
             model: "claude-3-5-haiku-latest",
             max_tokens: 1024,
-            system: "You are a translator. Translate the input to English. Output ONLY the English translation with proper capitalization and punctuation. Never add commentary, notes, or explanations. If the input is already in English, just clean up capitalization and punctuation.",
+            system: "You are a translator. Translate the input to English. Output ONLY the English translation with proper capitalization and punctuation. Never add commentary, notes, or explanations. If the input is already in English, just clean up capitalization and punctuation.".to_string(),
             messages: vec![Message {
                 role: "user",
                 content: raw_text.to_string(),
@@ -93,7 +93,8 @@ impl TextCleaner {
         Ok(translated)
     }
 
-    pub async fn cleanup(&self, raw_text: &str) -> Result<String> {
+    // `language` is what the transcriber detected, so the model doesn't "fix" it into English
+    pub async fn cleanup(&self, raw_text: &str, language: Option<&str>) -> Result<String> {
         if raw_text.trim().is_empty() {
             return Ok(String::new());
         }
@@ -104,7 +105,7 @@ impl TextCleaner {
         let request = ClaudeRequest {
             model: "claude-3-5-haiku-latest",
             max_tokens: 1024,
-            system: "You are a text formatting tool. You receive raw speech-to-text output and return ONLY the cleaned version. Fix capitalization and punctuation. Never add commentary, notes, apologies, or explanations. Never say 'I', never ask questions, never add parenthetical remarks. Output the cleaned text and nothing else.",
+            system: cleanup_prompt(language),
             messages: vec![Message {
                 role: "user",
                 content: raw_text.to_string(),
@@ -145,3 +146,14 @@ impl TextCleaner {
         Ok(cleaned)
     }
 }
+
+fn cleanup_prompt(language: Option<&str>) -> String {
+    let mut prompt = "You are a text formatting tool. You receive raw speech-to-text output and return ONLY the cleaned version. Fix capitalization and punctuation. Never add commentary, notes, apologies, or explanations. Never say 'I', never ask questions, never add parenthetical remarks. Output the cleaned text and nothing else.".to_string();
+    if let Some(language) = language {
+        prompt.push_str(&format!(
+            " The text is in the language with code '{}'; follow its punctuation and capitalization rules and never translate it.",
+            language
+        ));
+    }
+    prompt
+}
@@ -2,6 +2,7 @@ use crate::audio::list_input_devices;
 use crate::config::Config;
 use crate::AppState;
 use serde::{Deserialize, Serialize};
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
 use tauri::State;
 
@@ -11,6 +12,21 @@ pub struct AudioDevice {
     pub name: String,
 }
 
+// Optional backends compiled into this build, so the UI only offers what works
+#[derive(Debug, Serialize)]
+pub struct BuildFeatures {
+    pub local_whisper: bool,
+    pub opus: bool,
+}
+
+#[tauri::command]
+pub fn get_build_features() -> BuildFeatures {
+    BuildFeatures {
+        local_whisper: cfg!(feature = "local-whisper"),
+        opus: cfg!(feature = "opus"),
+    }
+}
+
 #[tauri::command]
 pub fn get_config(state: State<'_, Arc<AppState>>) -> Config {
     state.config.lock().unwrap().clone()
@@ -19,7 +35,14 @@ pub fn get_config(state: State<'_, Arc<AppState>>) -> Config {
 #[tauri::command]
 pub fn save_config(state: State<'_, Arc<AppState>>, config: Config) -> Result<(), String> {
     config.save().map_err(|e| e.to_string())?;
-    *state.config.lock().unwrap() = config;
+
+    let mut current = state.config.lock().unwrap();
+    if current.audio_stream_changed(&config) {
+        // Picked up by the input loop, which owns the audio stream
+        state.audio_config_changed.store(true, Ordering::SeqCst);
+    }
+    *current = config;
+
     Ok(())
 }
 
@@ -3,10 +3,106 @@ use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::PathBuf;
 
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TranscriptionBackend {
+    #[default]
+    ElevenLabs,
+    Local,
+    // Any server exposing the OpenAI /v1/audio/transcriptions API
+    OpenAi,
+}
+
+impl TranscriptionBackend {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::ElevenLabs => "ElevenLabs",
+            Self::Local => "local Whisper",
+            Self::OpenAi => "OpenAI-compatible",
+        }
+    }
+}
+
+// Container/codec used when uploading recordings
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AudioFormat {
+    #[default]
+    Wav,
+    // Lossless, roughly half the size of WAV for speech
+    Flac,
+    // Lossy Opus in an Ogg container, a fraction of the size
+    Opus,
+}
+
+// How finely ElevenLabs Scribe timestamps the transcript
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TimestampGranularity {
+    None,
+    #[default]
+    Word,
+    Character,
+}
+
+impl TimestampGranularity {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::None => "none",
+            Self::Word => "word",
+            Self::Character => "character",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
+    #[serde(default)]
+    pub backend: TranscriptionBackend,
+    // Tried in order when `backend` fails; a failing backend is skipped for the cool-down
+    #[serde(default)]
+    pub fallback_backends: Vec<TranscriptionBackend>,
+    #[serde(default = "default_fallback_cooldown_secs")]
+    pub fallback_cooldown_secs: u32,
+    // Also send the recording to the first fallback when `backend` hasn't answered in time
+    #[serde(default)]
+    pub hedge: bool,
+    #[serde(default = "default_hedge_delay_ms")]
+    pub hedge_delay_ms: u32,
     #[serde(default)]
     pub elevenlabs_api_key: String,
+    // Override for proxies or a local mock server
+    #[serde(default = "default_elevenlabs_base_url")]
+    pub elevenlabs_base_url: String,
+    #[serde(default)]
+    pub elevenlabs_format: AudioFormat,
+    #[serde(default = "default_elevenlabs_model_id")]
+    pub elevenlabs_model_id: String,
+    // Label who said what, for conversations rather than solo dictation
+    #[serde(default)]
+    pub elevenlabs_diarize: bool,
+    // Expected number of speakers; None lets Scribe work it out
+    #[serde(default)]
+    pub elevenlabs_num_speakers: Option<u32>,
+    // Mark non-speech sounds like (laughter) in the transcript
+    #[serde(default = "default_true")]
+    pub elevenlabs_tag_audio_events: bool,
+    #[serde(default)]
+    pub elevenlabs_timestamps: TimestampGranularity,
+    // Path to a GGML Whisper model (e.g. ggml-base.en.bin) for the local backend
+    #[serde(default)]
+    pub whisper_model_path: String,
+    #[serde(default = "default_openai_base_url")]
+    pub openai_base_url: String,
+    #[serde(default = "default_openai_model")]
+    pub openai_model: String,
+    #[serde(default)]
+    pub openai_api_key: String,
+    #[serde(default)]
+    pub openai_format: AudioFormat,
+    // Ask for verbose_json with word timings, which not every server supports
+    #[serde(default)]
+    pub openai_word_timestamps: bool,
     #[serde(default)]
     pub anthropic_api_key: String,
     #[serde(default = "default_language")]
@@ -17,14 +113,169 @@ pub struct Config {
     pub cleanup: bool,
     #[serde(default)]
     pub translate: bool,
+    // Start a new paragraph where the speaker paused this long (0 = off); needs word timings
     #[serde(default)]
+    pub paragraph_pause_ms: u32,
+    // Words recognized with less confidence than this are flagged in the log and UI
+    #[serde(default = "default_low_confidence_threshold")]
+    pub low_confidence_threshold: f32,
+    // Input device name; indices shift when USB devices come and go
+    #[serde(default)]
+    pub device_name: Option<String>,
+    // Index-based device setting from older configs, converted to a name on load
+    #[serde(default, skip_serializing)]
     pub device_index: Option<usize>,
+    // Input stream overrides; None uses the device's default config
+    #[serde(default)]
+    pub input_sample_rate: Option<u32>,
+    #[serde(default)]
+    pub input_channels: Option<u16>,
+    #[serde(default)]
+    pub input_buffer_frames: Option<u32>,
+    // 1-based input channels to record, mixed together; empty mixes every channel
+    #[serde(default)]
+    pub channel_selection: Vec<u16>,
+    // Audio kept from just before the trigger so the first syllable isn't clipped
+    #[serde(default = "default_preroll_ms")]
+    pub preroll_ms: u32,
+    // Recordings are downmixed to mono and resampled to this rate before upload
+    #[serde(default = "default_target_sample_rate")]
+    pub target_sample_rate: u32,
+    // Hard cap on a single recording in seconds (0 = unlimited)
+    #[serde(default = "default_max_recording_secs")]
+    pub max_recording_secs: u32,
+    // Longer recordings are split at silence into chunks of at most this many seconds
+    #[serde(default = "default_chunk_secs")]
+    pub chunk_secs: u32,
+    // Spectral gating for noisy rooms; strength from 0.0 (off) to 1.0 (mute the noise)
+    #[serde(default)]
+    pub noise_suppression: bool,
+    #[serde(default = "default_noise_suppression_strength")]
+    pub noise_suppression_strength: f32,
+    // Trim silence from both ends and skip transcription when nothing was said
+    #[serde(default = "default_true")]
+    pub vad: bool,
+    #[serde(default = "default_vad_threshold_db")]
+    pub vad_threshold_db: f32,
+    // Bring quiet and hot mics to a consistent level, with a limiter against clipping
+    #[serde(default = "default_true")]
+    pub normalize: bool,
+    #[serde(default = "default_normalize_target_db")]
+    pub normalize_target_db: f32,
+    // Hands-free: stop recording once speech is followed by this much silence
+    #[serde(default)]
+    pub auto_stop: bool,
+    #[serde(default = "default_auto_stop_silence_ms")]
+    pub auto_stop_silence_ms: u32,
+    // Keep the mic open until the next trigger and insert each utterance as it ends
+    #[serde(default)]
+    pub continuous: bool,
+    #[serde(default = "default_utterance_silence_ms")]
+    pub utterance_silence_ms: u32,
+    // Short sounds on state changes, for when the tray icon is out of sight
+    #[serde(default)]
+    pub sound_cues: bool,
+    #[serde(default = "default_cue_volume")]
+    pub cue_volume: f32,
+    #[serde(default = "default_true")]
+    pub cue_start: bool,
+    #[serde(default = "default_true")]
+    pub cue_stop: bool,
+    #[serde(default = "default_true")]
+    pub cue_success: bool,
+    #[serde(default = "default_true")]
+    pub cue_empty: bool,
+    #[serde(default = "default_true")]
+    pub cue_error: bool,
+    // Stream audio to a realtime endpoint while recording instead of uploading afterwards
+    #[serde(default)]
+    pub streaming: bool,
+    #[serde(default = "default_streaming_url")]
+    pub streaming_url: String,
+    #[serde(default = "default_streaming_model")]
+    pub streaming_model: String,
 }
 
 fn default_language() -> String {
     "auto".to_string()
 }
 
+fn default_fallback_cooldown_secs() -> u32 {
+    60
+}
+
+fn default_hedge_delay_ms() -> u32 {
+    1500
+}
+
+fn default_elevenlabs_base_url() -> String {
+    "https://api.elevenlabs.io/v1".to_string()
+}
+
+fn default_elevenlabs_model_id() -> String {
+    "scribe_v1".to_string()
+}
+
+fn default_openai_base_url() -> String {
+    "http://localhost:8000/v1".to_string()
+}
+
+fn default_openai_model() -> String {
+    "whisper-1".to_string()
+}
+
+fn default_streaming_url() -> String {
+    "wss://api.elevenlabs.io/v1/speech-to-text/realtime".to_string()
+}
+
+fn default_streaming_model() -> String {
+    "scribe_v2_realtime".to_string()
+}
+
+fn default_low_confidence_threshold() -> f32 {
+    0.5
+}
+
+fn default_preroll_ms() -> u32 {
+    300
+}
+
+fn default_target_sample_rate() -> u32 {
+    16_000
+}
+
+fn default_normalize_target_db() -> f32 {
+    -20.0
+}
+
+fn default_max_recording_secs() -> u32 {
+    600
+}
+
+fn default_chunk_secs() -> u32 {
+    60
+}
+
+fn default_noise_suppression_strength() -> f32 {
+    0.7
+}
+
+fn default_vad_threshold_db() -> f32 {
+    -50.0
+}
+
+fn default_auto_stop_silence_ms() -> u32 {
+    1500
+}
+
+fn default_utterance_silence_ms() -> u32 {
+    800
+}
+
+fn default_cue_volume() -> f32 {
+    0.5
+}
+
 fn default_true() -> bool {
     true
 }
@@ -32,13 +283,62 @@ fn default_true() -> bool {
 impl Default for Config {
     fn default() -> Self {
         Self {
+            backend: TranscriptionBackend::default(),
+            fallback_backends: Vec::new(),
+            fallback_cooldown_secs: default_fallback_cooldown_secs(),
+            hedge: false,
+            hedge_delay_ms: default_hedge_delay_ms(),
             elevenlabs_api_key: std::env::var("ELEVENLABS_API_KEY").unwrap_or_default(),
+            elevenlabs_base_url: default_elevenlabs_base_url(),
+            elevenlabs_format: AudioFormat::default(),
+            elevenlabs_model_id: default_elevenlabs_model_id(),
+            elevenlabs_diarize: false,
+            elevenlabs_num_speakers: None,
+            elevenlabs_tag_audio_events: true,
+            elevenlabs_timestamps: TimestampGranularity::default(),
+            whisper_model_path: String::new(),
+            openai_base_url: default_openai_base_url(),
+            openai_model: default_openai_model(),
+            openai_api_key: std::env::var("OPENAI_API_KEY").unwrap_or_default(),
+            openai_format: AudioFormat::default(),
+            openai_word_timestamps: false,
             anthropic_api_key: std::env::var("ANTHROPIC_API_KEY").unwrap_or_default(),
             language: default_language(),
             auto_enter: true,
             cleanup: false,
             translate: false,
+            paragraph_pause_ms: 0,
+            low_confidence_threshold: default_low_confidence_threshold(),
+            device_name: None,
             device_index: None,
+            input_sample_rate: None,
+            input_channels: None,
+            input_buffer_frames: None,
+            channel_selection: Vec::new(),
+            preroll_ms: default_preroll_ms(),
+            target_sample_rate: default_target_sample_rate(),
+            max_recording_secs: default_max_recording_secs(),
+            chunk_secs: default_chunk_secs(),
+            noise_suppression: false,
+            noise_suppression_strength: default_noise_suppression_strength(),
+            vad: true,
+            vad_threshold_db: default_vad_threshold_db(),
+            normalize: true,
+            normalize_target_db: default_normalize_target_db(),
+            auto_stop: false,
+            auto_stop_silence_ms: default_auto_stop_silence_ms(),
+            continuous: false,
+            utterance_silence_ms: default_utterance_silence_ms(),
+            sound_cues: false,
+            cue_volume: default_cue_volume(),
+            cue_start: true,
+            cue_stop: true,
+            cue_success: true,
+            cue_empty: true,
+            cue_error: true,
+            streaming: false,
+            streaming_url: default_streaming_url(),
+            streaming_model: default_streaming_model(),
         }
     }
 }
@@ -73,10 +373,47 @@ impl Config {
         if config.anthropic_api_key.is_empty() {
             config.anthropic_api_key = std::env::var("ANTHROPIC_API_KEY").unwrap_or_default();
         }
+        if config.openai_api_key.is_empty() {
+            config.openai_api_key = std::env::var("OPENAI_API_KEY").unwrap_or_default();
+        }
+
+        // Keep the device picked by index in older versions, stored by name from the next save
+        if let Some(index) = config.device_index.take() {
+            if config.device_name.is_none() {
+                config.device_name = crate::audio::list_input_devices()
+                    .into_iter()
+                    .nth(index)
+                    .map(|device| device.name);
+                match &config.device_name {
+                    Some(name) => tracing::info!("migrated audio device {} to '{}'", index, name),
+                    None => tracing::warn!("audio device {} from config not found, using default", index),
+                }
+            }
+        }
 
         Ok(config)
     }
 
+    // `backend` followed by its fallbacks, each listed once
+    pub fn backend_chain(&self) -> Vec<TranscriptionBackend> {
+        let mut chain = vec![self.backend];
+        for &backend in &self.fallback_backends {
+            if !chain.contains(&backend) {
+                chain.push(backend);
+            }
+        }
+        chain
+    }
+
+    // Whether moving to `other` requires rebuilding the audio stream
+    pub fn audio_stream_changed(&self, other: &Config) -> bool {
+        self.device_name != other.device_name
+            || self.input_sample_rate != other.input_sample_rate
+            || self.input_channels != other.input_channels
+            || self.input_buffer_frames != other.input_buffer_frames
+            || self.channel_selection != other.channel_selection
+    }
+
     pub fn save(&self) -> Result<()> {
         let path = Self::config_path()?;
         let contents = serde_json::to_string_pretty(self)?;
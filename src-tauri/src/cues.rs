@@ -0,0 +1,180 @@
+use crate::config::Config;
+use anyhow::{Context, Result};
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use cpal::{Device, FromSample, SampleFormat, SizedSample};
+use std::f32::consts::PI;
+use std::sync::mpsc::{self, Sender};
+use std::time::Duration;
+
+// Fade in/out per note so tones start and end without a click
+const FADE_MS: f32 = 5.0;
+// Peak level at full volume, well below full scale so cues never startle
+const CUE_PEAK: f32 = 0.5;
+// Extra time the stream stays open so the device can flush its last buffer
+const PLAYBACK_TAIL: Duration = Duration::from_millis(100);
+// Longest play_and_wait blocks, in case the output device hangs
+const MAX_WAIT: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cue {
+    Start,
+    Stop,
+    Success,
+    Empty,
+    Error,
+}
+
+impl Cue {
+    // (frequency in Hz, length in ms); a frequency of 0 is a pause
+    fn notes(self) -> &'static [(f32, f32)] {
+        match self {
+            Cue::Start => &[(660.0, 60.0), (880.0, 80.0)],
+            Cue::Stop => &[(880.0, 60.0), (660.0, 80.0)],
+            Cue::Success => &[(1046.5, 70.0)],
+            Cue::Empty => &[(440.0, 120.0)],
+            Cue::Error => &[(330.0, 90.0), (0.0, 60.0), (330.0, 90.0)],
+        }
+    }
+
+    fn enabled(self, config: &Config) -> bool {
+        let enabled = match self {
+            Cue::Start => config.cue_start,
+            Cue::Stop => config.cue_stop,
+            Cue::Success => config.cue_success,
+            Cue::Empty => config.cue_empty,
+            Cue::Error => config.cue_error,
+        };
+        config.sound_cues && enabled && config.cue_volume > 0.0
+    }
+}
+
+struct Request {
+    cue: Cue,
+    volume: f32,
+    // Signalled once the cue has finished playing
+    done: Option<Sender<()>>,
+}
+
+// Plays cues on a background thread so the input loop never waits on the output device
+pub struct CuePlayer {
+    tx: Sender<Request>,
+}
+
+impl CuePlayer {
+    pub fn new() -> Self {
+        let (tx, rx) = mpsc::channel::<Request>();
+        std::thread::spawn(move || {
+            for request in rx {
+                if let Err(e) = play(request.cue, request.volume) {
+                    tracing::warn!("failed to play {:?} cue: {}", request.cue, e);
+                }
+                if let Some(done) = request.done {
+                    let _ = done.send(());
+                }
+            }
+        });
+        Self { tx }
+    }
+
+    pub fn play(&self, config: &Config, cue: Cue) {
+        self.send(config, cue, None);
+    }
+
+    // Play `cue` and block until it has finished, so a recording started next doesn't hear it
+    pub fn play_and_wait(&self, config: &Config, cue: Cue) {
+        let (done, wait) = mpsc::channel();
+        if self.send(config, cue, Some(done)) {
+            let _ = wait.recv_timeout(MAX_WAIT);
+        }
+    }
+
+    fn send(&self, config: &Config, cue: Cue, done: Option<Sender<()>>) -> bool {
+        if !cue.enabled(config) {
+            return false;
+        }
+        let request = Request {
+            cue,
+            volume: config.cue_volume.clamp(0.0, 1.0),
+            done,
+        };
+        self.tx.send(request).is_ok()
+    }
+}
+
+// The stream is opened per cue, so the output device isn't held open between them
+fn play(cue: Cue, volume: f32) -> Result<()> {
+    let device = cpal::default_host()
+        .default_output_device()
+        .context("no output device available")?;
+    let supported = device.default_output_config()?;
+    let sample_format = supported.sample_format();
+    let config = supported.config();
+
+    let samples = synthesize(cue, config.sample_rate.0, volume);
+    let duration = Duration::from_secs_f32(samples.len() as f32 / config.sample_rate.0 as f32);
+
+    let stream = match sample_format {
+        SampleFormat::I8 => build_output_stream::<i8>(&device, &config, samples)?,
+        SampleFormat::I16 => build_output_stream::<i16>(&device, &config, samples)?,
+        SampleFormat::I32 => build_output_stream::<i32>(&device, &config, samples)?,
+        SampleFormat::I64 => build_output_stream::<i64>(&device, &config, samples)?,
+        SampleFormat::U8 => build_output_stream::<u8>(&device, &config, samples)?,
+        SampleFormat::U16 => build_output_stream::<u16>(&device, &config, samples)?,
+        SampleFormat::U32 => build_output_stream::<u32>(&device, &config, samples)?,
+        SampleFormat::U64 => build_output_stream::<u64>(&device, &config, samples)?,
+        SampleFormat::F32 => build_output_stream::<f32>(&device, &config, samples)?,
+        SampleFormat::F64 => build_output_stream::<f64>(&device, &config, samples)?,
+        other => anyhow::bail!("unsupported sample format: {}", other),
+    };
+
+    stream.play().context("failed to start output stream")?;
+    std::thread::sleep(duration + PLAYBACK_TAIL);
+    Ok(())
+}
+
+fn build_output_stream<T>(device: &Device, config: &cpal::StreamConfig, samples: Vec<f32>) -> Result<cpal::Stream>
+where
+    T: SizedSample + FromSample<f32>,
+{
+    let channels = config.channels as usize;
+    let mut position = 0;
+
+    let stream = device.build_output_stream(
+        config,
+        move |data: &mut [T], _| {
+            // Same mono cue on every channel, silence once it has played
+            for frame in data.chunks_mut(channels) {
+                let sample = samples.get(position).copied().unwrap_or(0.0);
+                position += 1;
+                frame.fill(T::from_sample(sample));
+            }
+        },
+        |err| tracing::warn!("cue output stream error: {}", err),
+        None,
+    )?;
+
+    Ok(stream)
+}
+
+// Mono sine tones with short fades, scaled by `volume`
+fn synthesize(cue: Cue, sample_rate: u32, volume: f32) -> Vec<f32> {
+    let rate = sample_rate as f32;
+    let fade = (FADE_MS / 1000.0 * rate).max(1.0);
+    let mut samples = Vec::new();
+
+    for &(freq, ms) in cue.notes() {
+        let len = (ms / 1000.0 * rate) as usize;
+        for i in 0..len {
+            let t = i as f32;
+            let envelope = (t / fade).min((len as f32 - t) / fade).min(1.0);
+            let sample = if freq > 0.0 {
+                (2.0 * PI * freq * t / rate).sin() * envelope * volume * CUE_PEAK
+            } else {
+                0.0
+            };
+            samples.push(sample);
+        }
+    }
+
+    samples
+}
@@ -0,0 +1,117 @@
+use anyhow::Result;
+use realfft::RealFftPlanner;
+use std::f32::consts::PI;
+
+// Analysis frames of roughly this length, rounded up to a power of two
+const FRAME_MS: u32 = 32;
+// Share of the quietest frames that make up the noise profile
+const NOISE_PERCENTILE: f32 = 0.1;
+// A bin must be this many times louder than the noise profile to pass the gate
+const GATE_THRESHOLD: f32 = 2.0;
+// How much of the previous frame's mask carries over, against "musical noise" artifacts
+const MASK_SMOOTHING: f32 = 0.5;
+
+// Stationary spectral gating: learn the noise spectrum from the quietest frames, then
+// attenuate every bin that doesn't rise clearly above it. A strength of 1.0 mutes gated
+// bins entirely, 0.0 leaves the audio untouched.
+pub fn spectral_gate(samples: &[f32], sample_rate: u32, strength: f32) -> Result<Vec<f32>> {
+    let frame_len = ((sample_rate * FRAME_MS / 1000) as usize).next_power_of_two();
+    let hop = frame_len / 2;
+    let strength = strength.clamp(0.0, 1.0);
+    if strength == 0.0 || samples.len() < frame_len * 4 {
+        return Ok(samples.to_vec());
+    }
+
+    // sqrt-Hann for both analysis and synthesis sums to one at 50% overlap
+    let window: Vec<f32> = (0..frame_len)
+        .map(|i| (PI * i as f32 / frame_len as f32).sin())
+        .collect();
+
+    // Pad so the first and last samples get full overlap-add coverage
+    let mut padded = vec![0.0f32; hop];
+    padded.extend_from_slice(samples);
+    padded.resize(padded.len().div_ceil(hop) * hop + hop, 0.0);
+    let frames = (padded.len() - frame_len) / hop + 1;
+
+    let mut planner = RealFftPlanner::<f32>::new();
+    let forward = planner.plan_fft_forward(frame_len);
+    let inverse = planner.plan_fft_inverse(frame_len);
+    let mut input = forward.make_input_vec();
+    let mut spectrum = forward.make_output_vec();
+    let mut output = inverse.make_output_vec();
+
+    let mut analyze = |frame: usize, spectrum: &mut [realfft::num_complex::Complex<f32>]| {
+        let start = frame * hop;
+        for (i, x) in input.iter_mut().enumerate() {
+            *x = padded[start + i] * window[i];
+        }
+        forward
+            .process(&mut input, spectrum)
+            .map_err(|e| anyhow::anyhow!("fft failed: {}", e))
+    };
+
+    // Noise profile: average magnitude per bin over the quietest frames
+    let mut energies: Vec<(usize, f32)> = (0..frames)
+        .map(|f| {
+            let frame = &padded[f * hop..f * hop + frame_len];
+            (f, frame.iter().map(|s| s * s).sum::<f32>())
+        })
+        .collect();
+    energies.sort_by(|a, b| a.1.total_cmp(&b.1));
+    let quiet = ((frames as f32 * NOISE_PERCENTILE) as usize).max(1);
+
+    let mut noise = vec![0.0f32; spectrum.len()];
+    for &(frame, _) in &energies[..quiet] {
+        analyze(frame, &mut spectrum)?;
+        for (n, value) in noise.iter_mut().zip(&spectrum) {
+            *n += value.norm() / quiet as f32;
+        }
+    }
+
+    let floor = 1.0 - strength;
+    let mut mask = vec![1.0f32; spectrum.len()];
+    let mut smoothed = vec![1.0f32; spectrum.len()];
+    let mut denoised = vec![0.0f32; padded.len()];
+
+    for frame in 0..frames {
+        analyze(frame, &mut spectrum)?;
+
+        for ((m, value), n) in mask.iter_mut().zip(&spectrum).zip(&noise) {
+            let target = if value.norm() > n * GATE_THRESHOLD {
+                1.0
+            } else {
+                floor
+            };
+            *m = MASK_SMOOTHING * *m + (1.0 - MASK_SMOOTHING) * target;
+        }
+
+        // Blur the mask across neighbouring bins as well
+        for (bin, value) in smoothed.iter_mut().enumerate() {
+            let lo = bin.saturating_sub(1);
+            let hi = (bin + 1).min(mask.len() - 1);
+            *value = mask[lo..=hi].iter().sum::<f32>() / (hi - lo + 1) as f32;
+        }
+
+        for (value, gain) in spectrum.iter_mut().zip(&smoothed) {
+            *value *= *gain;
+        }
+        // The inverse transform expects purely real DC and Nyquist bins
+        if let Some(first) = spectrum.first_mut() {
+            first.im = 0.0;
+        }
+        if let Some(last) = spectrum.last_mut() {
+            last.im = 0.0;
+        }
+
+        inverse
+            .process(&mut spectrum, &mut output)
+            .map_err(|e| anyhow::anyhow!("inverse fft failed: {}", e))?;
+
+        let start = frame * hop;
+        for (i, &x) in output.iter().enumerate() {
+            denoised[start + i] += x * window[i] / frame_len as f32;
+        }
+    }
+
+    Ok(denoised[hop..hop + samples.len()].to_vec())
+}
@@ -0,0 +1,35 @@
+use crate::config::TranscriptionBackend;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+// Remembers which backends failed recently so recordings skip them until the cool-down ends
+#[derive(Default)]
+pub struct CircuitBreaker {
+    open_until: Mutex<HashMap<TranscriptionBackend, Instant>>,
+}
+
+impl CircuitBreaker {
+    // Time left before `backend` is tried again, None when it is healthy
+    pub fn cooling_down(&self, backend: TranscriptionBackend) -> Option<Duration> {
+        let mut open_until = self.open_until.lock().unwrap();
+        let remaining = open_until.get(&backend)?.checked_duration_since(Instant::now());
+        if remaining.is_none() {
+            // Half-open: the next recording probes it, and a failure opens it again
+            open_until.remove(&backend);
+        }
+        remaining
+    }
+
+    pub fn record_success(&self, backend: TranscriptionBackend) {
+        self.open_until.lock().unwrap().remove(&backend);
+    }
+
+    // Retries already happened inside the request, so one failure is enough to open the circuit
+    pub fn record_failure(&self, backend: TranscriptionBackend, cool_down: Duration) {
+        self.open_until
+            .lock()
+            .unwrap()
+            .insert(backend, Instant::now() + cool_down);
+    }
+}
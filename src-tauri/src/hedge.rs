@@ -0,0 +1,187 @@
+use crate::config::TranscriptionBackend;
+use crate::transcribe::Transcript;
+use anyhow::Result;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Winner {
+    Primary,
+    Secondary,
+}
+
+pub struct RaceOutcome {
+    // None when both sides failed
+    pub winner: Option<Winner>,
+    // Whether secondary was started at all; if not, there was no race to count
+    pub hedged: bool,
+    pub result: Result<Transcript>,
+}
+
+// Start `primary`, then `secondary` as well once `delay` passes or primary fails. The first
+// non-empty success wins and the other future is dropped: that aborts an HTTP request and
+// stops local inference, but a model load already running finishes in the background.
+pub async fn race<P, S>(primary: P, secondary: S, delay: Duration) -> RaceOutcome
+where
+    P: Future<Output = Result<Transcript>>,
+    S: Future<Output = Result<Transcript>>,
+{
+    let hedge_timer = tokio::time::sleep(delay);
+    tokio::pin!(primary, secondary, hedge_timer);
+
+    let mut primary_result: Option<Result<Transcript>> = None;
+    let mut secondary_result: Option<Result<Transcript>> = None;
+    let mut hedged = false;
+
+    loop {
+        tokio::select! {
+            result = &mut primary, if primary_result.is_none() => {
+                if matches!(&result, Ok(transcript) if !transcript.is_empty()) {
+                    return RaceOutcome { winner: Some(Winner::Primary), hedged, result };
+                }
+                primary_result = Some(result);
+                hedged = true;
+            }
+            _ = &mut hedge_timer, if !hedged => {
+                tracing::info!("no result after {:?}, hedging", delay);
+                hedged = true;
+            }
+            result = &mut secondary, if hedged && secondary_result.is_none() => {
+                if matches!(&result, Ok(transcript) if !transcript.is_empty()) {
+                    return RaceOutcome { winner: Some(Winner::Secondary), hedged, result };
+                }
+                secondary_result = Some(result);
+            }
+        }
+
+        // Neither produced text: an empty transcript beats an error, primary beats secondary
+        if let (Some(primary), Some(secondary)) = (&primary_result, &secondary_result) {
+            let (winner, result) = match (primary, secondary) {
+                (Ok(_), _) => (Some(Winner::Primary), primary_result.unwrap()),
+                (Err(_), Ok(_)) => (Some(Winner::Secondary), secondary_result.unwrap()),
+                (Err(_), Err(_)) => (None, primary_result.unwrap()),
+            };
+            return RaceOutcome { winner, hedged, result };
+        }
+    }
+}
+
+// Per-backend latency and hedge results, kept for tuning the hedge delay
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BackendStats {
+    pub backend: &'static str,
+    pub requests: u32,
+    pub successes: u32,
+    pub races: u32,
+    pub wins: u32,
+    pub avg_latency_ms: u64,
+    #[serde(skip)]
+    total_latency_ms: u64,
+}
+
+#[derive(Default)]
+pub struct StatsRecorder {
+    stats: Mutex<HashMap<TranscriptionBackend, BackendStats>>,
+}
+
+impl StatsRecorder {
+    pub fn record_request(&self, backend: TranscriptionBackend, latency: Option<Duration>) {
+        self.update(backend, |stats| {
+            stats.requests += 1;
+            if let Some(latency) = latency {
+                stats.successes += 1;
+                stats.total_latency_ms += latency.as_millis() as u64;
+                stats.avg_latency_ms = stats.total_latency_ms / stats.successes as u64;
+            }
+        });
+    }
+
+    pub fn record_race(&self, backend: TranscriptionBackend, won: bool) {
+        self.update(backend, |stats| {
+            stats.races += 1;
+            if won {
+                stats.wins += 1;
+            }
+        });
+    }
+
+    pub fn snapshot(&self) -> Vec<BackendStats> {
+        let mut stats: Vec<BackendStats> = self.stats.lock().unwrap().values().cloned().collect();
+        stats.sort_by_key(|stats| stats.backend);
+        stats
+    }
+
+    fn update(&self, backend: TranscriptionBackend, f: impl FnOnce(&mut BackendStats)) {
+        let mut stats = self.stats.lock().unwrap();
+        let entry = stats.entry(backend).or_insert_with(|| BackendStats {
+            backend: backend.name(),
+            ..Default::default()
+        });
+        f(entry);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    const DELAY: Duration = Duration::from_millis(500);
+
+    async fn answer(after_ms: u64, result: Result<&str>) -> Result<Transcript> {
+        tokio::time::sleep(Duration::from_millis(after_ms)).await;
+        result.map(|text| Transcript::from_text(text.to_string()))
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn fast_primary_never_starts_secondary() {
+        let polled = AtomicBool::new(false);
+        let secondary = async {
+            polled.store(true, Ordering::SeqCst);
+            answer(0, Ok("secondary")).await
+        };
+
+        let outcome = race(answer(100, Ok("primary")), secondary, DELAY).await;
+        assert_eq!(outcome.winner, Some(Winner::Primary));
+        assert!(!outcome.hedged);
+        assert_eq!(outcome.result.unwrap().text, "primary");
+        assert!(!polled.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn hedge_fires_and_secondary_wins() {
+        let outcome = race(answer(2000, Ok("primary")), answer(300, Ok("secondary")), DELAY).await;
+        assert_eq!(outcome.winner, Some(Winner::Secondary));
+        assert!(outcome.hedged);
+        assert_eq!(outcome.result.unwrap().text, "secondary");
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn empty_primary_beats_failed_secondary() {
+        let outcome = race(
+            answer(100, Ok("")),
+            answer(100, Err(anyhow::anyhow!("secondary down"))),
+            DELAY,
+        )
+        .await;
+        assert_eq!(outcome.winner, Some(Winner::Primary));
+        assert!(outcome.hedged);
+        assert!(outcome.result.unwrap().is_empty());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn both_failing_returns_primary_error() {
+        let outcome = race(
+            answer(100, Err(anyhow::anyhow!("primary down"))),
+            answer(100, Err(anyhow::anyhow!("secondary down"))),
+            DELAY,
+        )
+        .await;
+        assert_eq!(outcome.winner, None);
+        assert!(outcome.hedged);
+        assert_eq!(outcome.result.unwrap_err().to_string(), "primary down");
+    }
+}
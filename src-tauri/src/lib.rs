@@ -2,9 +2,16 @@ mod audio;
 mod cleanup;
 mod commands;
 mod config;
+mod cues;
+mod denoise;
+mod fallback;
+mod hedge;
 mod input;
 mod output;
+mod streaming;
 mod transcribe;
+#[cfg(feature = "local-whisper")]
+mod whisper;
 
 use std::collections::VecDeque;
 use std::sync::atomic::{AtomicBool, Ordering};
@@ -12,17 +19,34 @@ use std::sync::{Arc, Mutex};
 use tauri::{
     image::Image,
     menu::{Menu, MenuItem},
-    tray::{MouseButton, MouseButtonState, TrayIcon, TrayIconBuilder, TrayIconEvent},
+    tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent},
     AppHandle, Emitter, Manager, RunEvent, WindowEvent,
 };
 
-use audio::AudioRecorder;
+use audio::{AudioClip, AudioEvent, AudioRecorder, SilenceSettings, StreamSettings};
 use cleanup::TextCleaner;
-use commands::{get_config, list_audio_devices, save_config};
-use config::Config;
+use commands::{get_build_features, get_config, list_audio_devices, save_config};
+use config::{Config, TranscriptionBackend};
+use cues::{Cue, CuePlayer};
+use fallback::CircuitBreaker;
+use hedge::{BackendStats, RaceOutcome, StatsRecorder, Winner};
 use input::{InputEvent, InputMonitor};
 use output::TextInserter;
-use transcribe::ElevenLabsClient;
+use streaming::{StreamEvent, StreamingOptions, StreamingSession};
+use transcribe::{create_transcriber, transcribe_long, TranscribeError, Transcript};
+
+// How often captured audio is forwarded to a realtime transcription session
+const STREAM_CHUNK_INTERVAL: std::time::Duration = std::time::Duration::from_millis(100);
+// How often to look for unplugged or reconnected audio devices while idle
+const DEVICE_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(2);
+// Peak level below which a recording most likely came from a muted or wrong device
+const SILENT_INPUT_DB: f32 = -60.0;
+// Share of full-scale samples above which the input is reported as clipping
+const CLIPPING_WARNING: f32 = 0.001;
+// Heads-up before a recording hits max_recording_secs
+const RECORDING_LIMIT_WARNING: std::time::Duration = std::time::Duration::from_secs(10);
+// Detected-language probability above which English speech is not sent for translation
+const ENGLISH_CONFIDENCE: f32 = 0.8;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
 pub enum AppStatus {
@@ -45,6 +69,12 @@ pub struct AppState {
     pub running: AtomicBool,
     pub logs: Mutex<VecDeque<LogEntry>>,
     pub status: Mutex<AppStatus>,
+    // Set by save_config when the audio stream needs rebuilding
+    pub audio_config_changed: AtomicBool,
+    // Transcription backends that failed recently, shared across recordings
+    pub circuit_breaker: CircuitBreaker,
+    // Latency and hedged-race results per backend
+    pub backend_stats: StatsRecorder,
 }
 
 impl AppState {
@@ -116,6 +146,11 @@ fn get_status(state: tauri::State<Arc<AppState>>) -> AppStatus {
     *state.status.lock().unwrap()
 }
 
+#[tauri::command]
+fn get_backend_stats(state: tauri::State<Arc<AppState>>) -> Vec<BackendStats> {
+    state.backend_stats.snapshot()
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tracing_subscriber::fmt()
@@ -135,6 +170,9 @@ pub fn run() {
         running: AtomicBool::new(true),
         logs: Mutex::new(VecDeque::new()),
         status: Mutex::new(AppStatus::Idle),
+        audio_config_changed: AtomicBool::new(false),
+        circuit_breaker: CircuitBreaker::default(),
+        backend_stats: StatsRecorder::default(),
     });
 
     state.add_log("INFO", "ezwhisper started");
@@ -150,6 +188,8 @@ pub fn run() {
             list_audio_devices,
             get_logs,
             get_status,
+            get_backend_stats,
+            get_build_features,
         ])
         .setup(move |app| {
             let handle = app.handle().clone();
@@ -229,7 +269,12 @@ fn run_input_loop(app: AppHandle, state: Arc<AppState>, tray_id: tauri::tray::Tr
         }
     };
 
-    let mut recorder = match AudioRecorder::new() {
+    let config = state.config.lock().unwrap().clone();
+    let mut recorder = match AudioRecorder::new(
+        config.device_name.as_deref(),
+        StreamSettings::from(&config),
+        config.preroll_ms,
+    ) {
         Ok(r) => Some(r),
         Err(e) => {
             tracing::error!("failed to create audio recorder: {}", e);
@@ -238,40 +283,159 @@ fn run_input_loop(app: AppHandle, state: Arc<AppState>, tray_id: tauri::tray::Tr
         }
     };
 
-    let rt = tokio::runtime::Runtime::new().unwrap();
+    let rt = Arc::new(tokio::runtime::Runtime::new().unwrap());
+    let cues = Arc::new(CuePlayer::new());
+    let utterances = UtteranceWorker::spawn(
+        app.clone(),
+        state.clone(),
+        rt.clone(),
+        cues.clone(),
+        tray_id.clone(),
+    );
     let mut status = AppStatus::Idle;
 
     tracing::info!("input monitoring started");
     state.add_log("INFO", "input monitoring started - click trigger to record");
 
     // Helper to update tray icon based on status
-    let update_icon = |app: &AppHandle, status: AppStatus| {
-        if let Some(tray) = app.tray_by_id(&tray_id) {
-            let icon = match status {
-                AppStatus::Idle => create_idle_icon(),
-                AppStatus::Recording => create_recording_icon(),
-                AppStatus::Transcribing => create_transcribing_icon(),
-            };
-            let _ = tray.set_icon(Some(icon));
-        }
-    };
+    let update_icon = |app: &AppHandle, status: AppStatus| update_tray_icon(app, &tray_id, status);
+
+    let mut session: Option<StreamingSession> = None;
+    let mut last_stream_send = std::time::Instant::now();
+    let mut last_device_check = std::time::Instant::now();
+    let mut recording_peak_db = f32::NEG_INFINITY;
+    let mut speech_ended = false;
+    let mut continuous = false;
+    let mut recording_started = std::time::Instant::now();
+    let mut max_recording: Option<std::time::Duration> = None;
+    let mut limit_warned = false;
 
     while state.running.load(Ordering::SeqCst) {
-        if let Some(event) = input_monitor.try_recv() {
+        // Switch devices between recordings, never in the middle of one
+        if status == AppStatus::Idle {
+            if state.audio_config_changed.swap(false, Ordering::SeqCst) {
+                apply_audio_config(&app, &state, &mut recorder);
+                last_device_check = std::time::Instant::now();
+            } else if last_device_check.elapsed() >= DEVICE_CHECK_INTERVAL {
+                check_audio_device(&app, &state, &mut recorder);
+                last_device_check = std::time::Instant::now();
+            }
+        }
+
+        // Level meter updates from the audio thread
+        if let Some(rec) = recorder.as_ref() {
+            while let Some(event) = rec.try_recv() {
+                match event {
+                    AudioEvent::Level(level) => {
+                        recording_peak_db = recording_peak_db.max(level.peak_db);
+                        let _ = app.emit("audio-level", level);
+                    }
+                    AudioEvent::SpeechStarted => tracing::debug!("speech started"),
+                    AudioEvent::SpeechEnded => speech_ended = true,
+                }
+            }
+        }
+
+        // Forward captured audio to the realtime session while recording
+        if let (Some(session), Some(rec)) = (session.as_mut(), recorder.as_mut()) {
+            if last_stream_send.elapsed() >= STREAM_CHUNK_INTERVAL {
+                session.send_audio(&rec.read_new_samples());
+                last_stream_send = std::time::Instant::now();
+            }
+        }
+
+        // Continuous dictation: each utterance is queued for transcription as soon as it ends
+        if continuous && status == AppStatus::Recording && std::mem::take(&mut speech_ended) {
+            if let Some(ref mut rec) = recorder {
+                // Only the last utterance may press Enter, or every sentence would be sent
+                let config = Config {
+                    auto_enter: false,
+                    ..state.config.lock().unwrap().clone()
+                };
+                if let Some(clip) = prepare_clip(&state, &config, Ok(rec.take_clip())) {
+                    utterances.submit(clip, config);
+                }
+            }
+        }
+
+        // Hard cap on recording length, with a heads-up shortly before
+        let mut limit_reached = false;
+        if let Some(max) = max_recording.filter(|_| status == AppStatus::Recording) {
+            let elapsed = recording_started.elapsed();
+            if !limit_warned && elapsed + RECORDING_LIMIT_WARNING >= max {
+                limit_warned = true;
+                let remaining = max.saturating_sub(elapsed).as_secs();
+                tracing::warn!("recording limit: stopping in {}s", remaining);
+                state.add_log("WARN", &format!("recording limit: stopping in {}s", remaining));
+                let _ = app.emit("recording-limit", remaining);
+            }
+            limit_reached = elapsed >= max;
+        }
+
+        // Hands-free mode ends the recording on trailing silence, like a second trigger press
+        let event = input_monitor.try_recv().or_else(|| {
+            let auto_stop = status == AppStatus::Recording && !continuous && std::mem::take(&mut speech_ended);
+            if auto_stop {
+                tracing::info!("silence detected, stopping");
+                state.add_log("INFO", "silence detected, stopping");
+            } else if limit_reached {
+                tracing::warn!("recording limit reached, stopping");
+                state.add_log("WARN", "recording limit reached, stopping");
+            }
+            (auto_stop || limit_reached).then_some(InputEvent::TriggerPressed)
+        });
+
+        if let Some(event) = event {
             if matches!(event, InputEvent::TriggerPressed) {
                 match status {
                     AppStatus::Idle => {
                         if let Some(ref mut rec) = recorder {
+                            let config = state.config.lock().unwrap().clone();
+                            rec.set_preroll_ms(config.preroll_ms);
+                            continuous = config.continuous;
+                            let silence_ms = if continuous {
+                                Some(config.utterance_silence_ms)
+                            } else {
+                                config.auto_stop.then_some(config.auto_stop_silence_ms)
+                            };
+                            rec.set_silence_detection(
+                                silence_ms.map(|silence_ms| SilenceSettings { silence_ms }),
+                            );
+                            speech_ended = false;
+
+                            // The start cue finishes before recording so neither the take nor the
+                            // speech detector hears it; pre-roll from before the click is kept
+                            rec.hold_preroll();
+                            cues.play_and_wait(&config, Cue::Start);
+
                             if let Err(e) = rec.start() {
                                 tracing::error!("failed to start recording: {}", e);
                                 state.add_log("ERROR", &format!("failed to start recording: {}", e));
+                                cues.play(&config, Cue::Error);
                             } else {
                                 status = AppStatus::Recording;
+                                recording_peak_db = f32::NEG_INFINITY;
+                                recording_started = std::time::Instant::now();
+                                limit_warned = false;
+                                // Continuous dictation hands audio off per utterance, so it has no cap
+                                max_recording = (config.max_recording_secs > 0 && !continuous)
+                                    .then(|| std::time::Duration::from_secs(config.max_recording_secs as u64));
                                 *state.status.lock().unwrap() = status;
                                 update_icon(&app, status);
-                                tracing::info!("recording...");
-                                state.add_log("INFO", "recording...");
+                                if continuous {
+                                    tracing::info!("continuous dictation started");
+                                    state.add_log("INFO", "continuous dictation started - click trigger to end");
+                                } else {
+                                    tracing::info!("recording...");
+                                    state.add_log("INFO", "recording...");
+                                }
                                 let _ = app.emit("status-changed", status);
+
+                                // Utterances are uploaded one by one in continuous mode
+                                if config.streaming && !continuous {
+                                    session = start_streaming(&app, &state, &rt, &config, rec.sample_rate());
+                                    last_stream_send = std::time::Instant::now();
+                                }
                             }
                         }
                     }
@@ -284,57 +448,61 @@ fn run_input_loop(app: AppHandle, state: Arc<AppState>, tray_id: tauri::tray::Tr
                         let _ = app.emit("status-changed", status);
 
                         let config = state.config.lock().unwrap().clone();
-                        let audio_data = recorder.as_mut().and_then(|rec| rec.stop().ok());
+                        cues.play(&config, Cue::Stop);
 
-                        if let Some(audio_data) = audio_data {
-                            if !audio_data.is_empty() {
-                                let client = ElevenLabsClient::new(
-                                    config.elevenlabs_api_key.clone(),
-                                    config.language.clone(),
-                                );
+                        if recording_peak_db < SILENT_INPUT_DB {
+                            let device = recorder.as_ref().map(|rec| rec.device_name()).unwrap_or_default();
+                            tracing::warn!("no input signal from {}, is the microphone muted?", device);
+                            state.add_log("WARN", &format!("no input signal from {}, is the microphone muted?", device));
+                        }
 
-                                let cleaner = if config.anthropic_api_key.is_empty() {
-                                    None
-                                } else {
-                                    Some(TextCleaner::new(config.anthropic_api_key.clone()))
-                                };
-
-                                let result = rt.block_on(async {
-                                    client.transcribe(audio_data).await
-                                });
-
-                                match result {
-                                    Ok(text) if !text.is_empty() => {
-                                        let final_text = if config.translate && cleaner.is_some() {
-                                            rt.block_on(async {
-                                                cleaner.as_ref().unwrap().translate(&text).await
-                                            }).unwrap_or(text)
-                                        } else if config.cleanup && cleaner.is_some() {
-                                            rt.block_on(async {
-                                                cleaner.as_ref().unwrap().cleanup(&text).await
-                                            }).unwrap_or(text)
-                                        } else {
-                                            text
-                                        };
-
-                                        tracing::info!("inserting: {}", final_text);
-                                        state.add_log("INFO", &format!("inserting: {}", final_text));
-                                        let inserter = TextInserter::new(config.auto_enter);
-                                        if let Err(e) = inserter.insert(&final_text) {
-                                            tracing::error!("failed to insert text: {}", e);
-                                            state.add_log("ERROR", &format!("failed to insert text: {}", e));
-                                        }
-                                    }
-                                    Ok(_) => {
-                                        tracing::warn!("empty transcription");
-                                        state.add_log("WARN", "empty transcription");
-                                    }
-                                    Err(e) => {
-                                        tracing::error!("transcription failed: {}", e);
-                                        state.add_log("ERROR", &format!("transcription failed: {}", e));
-                                    }
+                        // The realtime transcript is ready almost immediately after the last chunk
+                        let streamed = session.take().map(|mut session| {
+                            if let Some(ref mut rec) = recorder {
+                                session.send_audio(&rec.read_new_samples());
+                            }
+                            rt.block_on(session.finish())
+                        });
+
+                        let clip = recorder
+                            .as_mut()
+                            .and_then(|rec| prepare_clip(&state, &config, rec.stop()));
+
+                        // The rest of the take goes in after every utterance still in the queue
+                        let utterances_inserted = continuous && utterances.flush();
+
+                        let result = match streamed {
+                            Some(Ok(text)) if !text.is_empty() => Some(Ok(Transcript::from_text(text))),
+                            // Nothing committed yet is no reason to drop the recording
+                            Some(Ok(_)) => {
+                                tracing::info!("realtime transcript was empty, uploading instead");
+                                None
+                            }
+                            Some(Err(e)) => {
+                                tracing::warn!("realtime transcription failed, uploading instead: {}", e);
+                                state.add_log("WARN", &format!("realtime transcription failed, uploading instead: {}", e));
+                                None
+                            }
+                            None => None,
+                        };
+
+                        let result = result.or_else(|| clip.map(|clip| transcribe_clip(&state, &rt, &config, clip)));
+
+                        match (take_end(result.as_ref(), utterances_inserted), result) {
+                            (TakeEnd::InsertRest, Some(result)) => {
+                                insert_transcription(&app, &state, &rt, &cues, &config, result, "");
+                            }
+                            (TakeEnd::AlreadyInserted, _) => {
+                                if let Err(e) = TextInserter::new(config.auto_enter).press_return() {
+                                    tracing::error!("failed to press return: {}", e);
+                                    state.add_log("ERROR", &format!("failed to press return: {}", e));
                                 }
                             }
+                            _ => {
+                                tracing::warn!("no speech detected, skipping transcription");
+                                state.add_log("WARN", "no speech detected, skipping transcription");
+                                cues.play(&config, Cue::Empty);
+                            }
                         }
 
                         status = AppStatus::Idle;
@@ -354,3 +522,527 @@ fn run_input_loop(app: AppHandle, state: Arc<AppState>, tray_id: tauri::tray::Tr
         std::thread::sleep(std::time::Duration::from_millis(1));
     }
 }
+
+fn update_tray_icon(app: &AppHandle, tray_id: &tauri::tray::TrayIconId, status: AppStatus) {
+    if let Some(tray) = app.tray_by_id(tray_id) {
+        let icon = match status {
+            AppStatus::Idle => create_idle_icon(),
+            AppStatus::Recording => create_recording_icon(),
+            AppStatus::Transcribing => create_transcribing_icon(),
+        };
+        let _ = tray.set_icon(Some(icon));
+    }
+}
+
+enum UtteranceJob {
+    Transcribe(AudioClip, Box<Config>),
+    // Answered once every utterance queued before it has been inserted
+    Flush(std::sync::mpsc::Sender<()>),
+}
+
+// Transcribes and inserts continuous-dictation utterances in order, on its own thread,
+// so retries and fallbacks never hold up the input loop
+struct UtteranceWorker {
+    jobs: std::sync::mpsc::Sender<UtteranceJob>,
+    // Set once an utterance of the current take has been pasted, cleared by flush()
+    inserted: Arc<AtomicBool>,
+}
+
+impl UtteranceWorker {
+    fn spawn(
+        app: AppHandle,
+        state: Arc<AppState>,
+        rt: Arc<tokio::runtime::Runtime>,
+        cues: Arc<CuePlayer>,
+        tray_id: tauri::tray::TrayIconId,
+    ) -> Self {
+        let (jobs, rx) = std::sync::mpsc::channel();
+        let inserted = Arc::new(AtomicBool::new(false));
+        let worker_inserted = Arc::clone(&inserted);
+        std::thread::spawn(move || {
+            for job in rx {
+                match job {
+                    UtteranceJob::Transcribe(clip, config) => {
+                        update_tray_icon(&app, &tray_id, AppStatus::Transcribing);
+                        let result = transcribe_clip(&state, &rt, &config, clip);
+                        if insert_transcription(&app, &state, &rt, &cues, &config, result, " ") {
+                            worker_inserted.store(true, Ordering::SeqCst);
+                        }
+                        // Recording again, unless the take ended while this one was in flight
+                        let status = *state.status.lock().unwrap();
+                        update_tray_icon(&app, &tray_id, status);
+                    }
+                    UtteranceJob::Flush(done) => {
+                        let _ = done.send(());
+                    }
+                }
+            }
+        });
+
+        Self { jobs, inserted }
+    }
+
+    fn submit(&self, clip: AudioClip, config: Config) {
+        let _ = self.jobs.send(UtteranceJob::Transcribe(clip, Box::new(config)));
+    }
+
+    // Block until every utterance submitted so far has been inserted.
+    // Returns whether any of them actually put text in, and starts the next take afresh.
+    fn flush(&self) -> bool {
+        let (done, wait) = std::sync::mpsc::channel();
+        if self.jobs.send(UtteranceJob::Flush(done)).is_ok() {
+            let _ = wait.recv();
+        }
+        self.inserted.swap(false, Ordering::SeqCst)
+    }
+}
+
+// How a take ends once whatever was left after the last utterance has been transcribed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TakeEnd {
+    // Insert the rest; auto-enter follows the paste as usual
+    InsertRest,
+    // Continuous dictation already pasted everything, only the held-back Return is left
+    AlreadyInserted,
+    NoSpeech,
+}
+
+fn take_end(rest: Option<&anyhow::Result<Transcript>>, utterances_inserted: bool) -> TakeEnd {
+    match rest {
+        // A trailing breath or click that transcribed to nothing doesn't undo the take
+        Some(Ok(transcript)) if transcript.is_empty() && utterances_inserted => TakeEnd::AlreadyInserted,
+        Some(_) => TakeEnd::InsertRest,
+        None if utterances_inserted => TakeEnd::AlreadyInserted,
+        None => TakeEnd::NoSpeech,
+    }
+}
+
+// Resample and trim a finished recording; None when there is nothing worth sending
+fn prepare_clip(state: &AppState, config: &Config, clip: anyhow::Result<AudioClip>) -> Option<AudioClip> {
+    let clip = clip
+        .inspect(|clip| warn_if_clipped(state, clip))
+        .and_then(|clip| clip.resample(config.target_sample_rate))
+        // Before VAD, so trimming sees speech against a cleaner background
+        .and_then(|clip| {
+            if config.noise_suppression {
+                clip.denoise(config.noise_suppression_strength)
+            } else {
+                Ok(clip)
+            }
+        })
+        .map_err(|e| {
+            tracing::error!("failed to prepare audio: {}", e);
+            state.add_log("ERROR", &format!("failed to prepare audio: {}", e));
+        })
+        .ok()
+        .filter(|clip| !clip.is_empty())?;
+
+    // Level first, so a quiet microphone isn't mistaken for silence by the VAD threshold
+    let clip = if config.normalize {
+        clip.normalize(config.normalize_target_db)
+    } else {
+        clip
+    };
+
+    if config.vad {
+        return clip.trim_silence(config.vad_threshold_db);
+    }
+    Some(clip)
+}
+
+fn warn_if_clipped(state: &AppState, clip: &AudioClip) {
+    let clipped = clip.clipped_fraction();
+    if clipped > CLIPPING_WARNING {
+        tracing::warn!("input clipped ({:.1}% of samples), lower the microphone gain", clipped * 100.0);
+        state.add_log(
+            "WARN",
+            &format!("input clipped ({:.1}% of samples), lower the microphone gain", clipped * 100.0),
+        );
+    }
+}
+
+// Try each configured backend in turn, skipping ones still cooling down after a failure
+fn transcribe_clip(
+    state: &AppState,
+    rt: &tokio::runtime::Runtime,
+    config: &Config,
+    clip: AudioClip,
+) -> anyhow::Result<Transcript> {
+    tracing::info!("transcribing {:.1}s of audio", clip.duration_secs());
+
+    let chain = config.backend_chain();
+    let healthy: Vec<TranscriptionBackend> = chain
+        .iter()
+        .copied()
+        .filter(|&backend| match state.circuit_breaker.cooling_down(backend) {
+            Some(remaining) => {
+                tracing::info!("skipping {} backend for another {}s", backend.name(), remaining.as_secs());
+                false
+            }
+            None => true,
+        })
+        .collect();
+    // Better to retry a backend that failed recently than to give up without trying
+    let candidates = if healthy.is_empty() {
+        if chain.len() > 1 {
+            tracing::warn!("every backend is cooling down, trying them anyway");
+            state.add_log("WARN", "every backend is cooling down, trying them anyway");
+        }
+        chain
+    } else {
+        healthy
+    };
+
+    let mut remaining = candidates.as_slice();
+    let mut last_error = None;
+
+    // Hedged: race the first two backends, then fall back to the rest if both fail
+    if let [primary, secondary, rest @ ..] = remaining {
+        if config.hedge {
+            let delay = std::time::Duration::from_millis(config.hedge_delay_ms as u64);
+            let RaceOutcome { winner, hedged, result } = rt.block_on(hedge::race(
+                transcribe_with(state, config, &clip, *primary),
+                transcribe_with(state, config, &clip, *secondary),
+                delay,
+            ));
+            // A primary that answered before the delay never raced anyone
+            if hedged {
+                state.backend_stats.record_race(*primary, winner == Some(Winner::Primary));
+                state.backend_stats.record_race(*secondary, winner == Some(Winner::Secondary));
+            }
+
+            match result {
+                Ok(transcript) => {
+                    let backend = if winner == Some(Winner::Secondary) { secondary } else { primary };
+                    tracing::info!("{} backend won the race", backend.name());
+                    return Ok(transcript);
+                }
+                Err(e) => {
+                    if let Some(next) = rest.first() {
+                        tracing::warn!("both hedged backends failed, trying {}: {:#}", next.name(), e);
+                        state.add_log(
+                            "WARN",
+                            &format!("both hedged backends failed, trying {}: {:#}", next.name(), e),
+                        );
+                    }
+                    last_error = Some(e);
+                    remaining = rest;
+                }
+            }
+        }
+    }
+
+    for (i, &backend) in remaining.iter().enumerate() {
+        let e = match rt.block_on(transcribe_with(state, config, &clip, backend)) {
+            Ok(transcript) => return Ok(transcript),
+            Err(e) => e,
+        };
+
+        if let Some(next) = remaining.get(i + 1) {
+            tracing::warn!("{} backend failed, trying {}: {:#}", backend.name(), next.name(), e);
+            state.add_log(
+                "WARN",
+                &format!("{} backend failed, trying {}: {:#}", backend.name(), next.name(), e),
+            );
+        }
+        last_error = Some(e);
+    }
+
+    Err(last_error.expect("backend chain is never empty"))
+}
+
+// One backend's attempt at the clip, recorded in the circuit breaker and backend stats
+async fn transcribe_with(
+    state: &AppState,
+    config: &Config,
+    clip: &AudioClip,
+    backend: TranscriptionBackend,
+) -> anyhow::Result<Transcript> {
+    let start = std::time::Instant::now();
+
+    // Loading a local model blocks, which would hold up the other side of a hedged race
+    let owned = config.clone();
+    let result = match tokio::task::spawn_blocking(move || create_transcriber(&owned, backend)).await {
+        Ok(Ok(transcriber)) => {
+            tracing::info!("using {} backend", transcriber.name());
+            transcribe_long(transcriber.as_ref(), clip, config.chunk_secs).await
+        }
+        Ok(Err(e)) => Err(e),
+        Err(e) => Err(e.into()),
+    };
+
+    match &result {
+        Ok(_) => {
+            state.circuit_breaker.record_success(backend);
+            state.backend_stats.record_request(backend, Some(start.elapsed()));
+        }
+        Err(e) => {
+            // Too large is about this recording, not the backend's health
+            let clip_specific = matches!(
+                e.chain().find_map(|e| e.downcast_ref::<TranscribeError>()),
+                Some(TranscribeError::PayloadTooLarge)
+            );
+            if !clip_specific {
+                let cool_down = std::time::Duration::from_secs(config.fallback_cooldown_secs as u64);
+                state.circuit_breaker.record_failure(backend, cool_down);
+            }
+            state.backend_stats.record_request(backend, None);
+        }
+    }
+
+    result
+}
+
+// Optional cleanup/translation, then paste into the focused app followed by `separator`.
+// Returns whether any text went in.
+fn insert_transcription(
+    app: &AppHandle,
+    state: &AppState,
+    rt: &tokio::runtime::Runtime,
+    cues: &CuePlayer,
+    config: &Config,
+    result: anyhow::Result<Transcript>,
+    separator: &str,
+) -> bool {
+    let cleaner = if config.anthropic_api_key.is_empty() {
+        None
+    } else {
+        Some(TextCleaner::new(config.anthropic_api_key.clone()))
+    };
+
+    match result {
+        Ok(transcript) if !transcript.is_empty() => {
+            log_transcript(state, config, &transcript);
+            let _ = app.emit("transcript", &transcript);
+
+            // A conversation reads as one paragraph per speaker turn
+            let text = if let Some(text) = transcript.speaker_text() {
+                text
+            } else if config.paragraph_pause_ms > 0 && !transcript.words.is_empty() {
+                transcript.paragraphs(config.paragraph_pause_ms as f32 / 1000.0)
+            } else {
+                transcript.text.clone()
+            };
+            let language = transcript.language_code.as_deref();
+
+            // Speech already in English only needs the tidy-up that translation would have done
+            let translate = config.translate && !transcript.is_english(ENGLISH_CONFIDENCE);
+            let final_text = match cleaner.as_ref() {
+                Some(cleaner) if translate => rt.block_on(cleaner.translate(&text)).unwrap_or(text),
+                Some(cleaner) if config.cleanup || config.translate => {
+                    rt.block_on(cleaner.cleanup(&text, language)).unwrap_or(text)
+                }
+                _ => text,
+            };
+
+            tracing::info!("inserting: {}", final_text);
+            state.add_log("INFO", &format!("inserting: {}", final_text));
+            let inserter = TextInserter::new(config.auto_enter);
+            // No chime right as auto-enter sends the message; the Return is feedback enough
+            if !config.auto_enter {
+                cues.play(config, Cue::Success);
+            }
+            if let Err(e) = inserter.insert(&format!("{}{}", final_text, separator)) {
+                tracing::error!("failed to insert text: {}", e);
+                state.add_log("ERROR", &format!("failed to insert text: {}", e));
+                cues.play(config, Cue::Error);
+                return false;
+            }
+            true
+        }
+        Ok(_) => {
+            tracing::warn!("empty transcription");
+            state.add_log("WARN", "empty transcription");
+            cues.play(config, Cue::Empty);
+            false
+        }
+        Err(e) => {
+            // API failures come with advice instead of the provider's raw response
+            let message = match e.chain().find_map(|e| e.downcast_ref::<TranscribeError>()) {
+                Some(api_error) => format!("transcription failed: {:#}, {}", e, api_error.hint()),
+                None => format!("transcription failed: {:#}", e),
+            };
+            tracing::error!("{}", message);
+            state.add_log("ERROR", &message);
+            let _ = app.emit("transcription-error", message);
+            cues.play(config, Cue::Error);
+            false
+        }
+    }
+}
+
+// Detected language and words the backend was unsure about, to help spot misrecognitions
+fn log_transcript(state: &AppState, config: &Config, transcript: &Transcript) {
+    if let Some(language) = &transcript.language_code {
+        let message = match transcript.language_probability {
+            Some(probability) => format!("detected language: {} ({:.0}%)", language, probability * 100.0),
+            None => format!("detected language: {}", language),
+        };
+        tracing::info!("{}", message);
+        state.add_log("INFO", &message);
+    }
+
+    let mut speakers: Vec<&str> = transcript.segments.iter().map(|segment| segment.speaker.as_str()).collect();
+    speakers.sort_unstable();
+    speakers.dedup();
+    if speakers.len() > 1 {
+        tracing::info!("{} speakers in {} turns", speakers.len(), transcript.segments.len());
+        state.add_log(
+            "INFO",
+            &format!("{} speakers in {} turns", speakers.len(), transcript.segments.len()),
+        );
+    }
+
+    let unsure: Vec<&str> = transcript
+        .low_confidence_words(config.low_confidence_threshold)
+        .map(|word| word.text.as_str())
+        .collect();
+    if !unsure.is_empty() {
+        tracing::info!("low-confidence words: {}", unsure.join(", "));
+        state.add_log("INFO", &format!("low-confidence words: {}", unsure.join(", ")));
+    }
+}
+
+// Rebuild the audio stream after the device was changed in settings
+fn apply_audio_config(app: &AppHandle, state: &AppState, recorder: &mut Option<AudioRecorder>) {
+    let config = state.config.lock().unwrap().clone();
+    let device_name = config.device_name.as_deref();
+
+    let settings = StreamSettings::from(&config);
+
+    // Release the old stream before opening the new one; hw: devices only allow one at a time.
+    // If the rebuild fails, check_audio_device keeps retrying and falls back to the default.
+    *recorder = None;
+    let result = AudioRecorder::new(device_name, settings, config.preroll_ms).map(|rec| {
+        *recorder = Some(rec);
+    });
+
+    match result {
+        Ok(()) => {
+            let name = recorder.as_ref().map(|rec| rec.device_name()).unwrap_or_default();
+            tracing::info!("using audio device: {}", name);
+            state.add_log("INFO", &format!("using audio device: {}", name));
+            let _ = app.emit("audio-device-changed", name);
+        }
+        Err(e) => {
+            tracing::error!("failed to switch audio device: {}", e);
+            state.add_log("ERROR", &format!("failed to switch audio device: {}", e));
+        }
+    }
+}
+
+// Recover from unplugged devices and move back to the configured one once it returns
+fn check_audio_device(app: &AppHandle, state: &AppState, recorder: &mut Option<AudioRecorder>) {
+    let config = state.config.lock().unwrap().clone();
+
+    let Some(rec) = recorder.as_ref() else {
+        // Keep retrying quietly until some input device shows up
+        match AudioRecorder::new(
+            config.device_name.as_deref(),
+            StreamSettings::from(&config),
+            config.preroll_ms,
+        ) {
+            Ok(rec) => {
+                tracing::info!("audio device available: {}", rec.device_name());
+                state.add_log("INFO", &format!("audio device available: {}", rec.device_name()));
+                let _ = app.emit("audio-device-changed", rec.device_name());
+                *recorder = Some(rec);
+            }
+            Err(e) => tracing::debug!("no usable audio device yet: {}", e),
+        }
+        return;
+    };
+
+    // Only the stream error callback says whether our device is gone. ALSA can't enumerate a
+    // hw: device we hold open (EBUSY), so a missing name here would be a false alarm.
+    if rec.is_failed() {
+        let lost = rec.device_name().to_string();
+        tracing::warn!("audio device lost: {}", lost);
+        state.add_log("WARN", &format!("audio device lost: {}", lost));
+        let _ = app.emit("audio-device-lost", &lost);
+
+        // Drop the dead stream, then fall back to whatever is available
+        *recorder = None;
+        check_audio_device(app, state, recorder);
+        return;
+    }
+
+    // We fell back earlier and the configured device is back
+    if let Some(name) = config.device_name {
+        if name != rec.device_name() && audio::input_device_available(&name) {
+            tracing::info!("audio device reconnected: {}", name);
+            state.add_log("INFO", &format!("audio device reconnected: {}", name));
+            apply_audio_config(app, state, recorder);
+        }
+    }
+}
+
+// Open a realtime session that emits partial/final transcripts to the UI
+fn start_streaming(
+    app: &AppHandle,
+    state: &AppState,
+    rt: &tokio::runtime::Runtime,
+    config: &Config,
+    input_sample_rate: u32,
+) -> Option<StreamingSession> {
+    let options = StreamingOptions {
+        url: config.streaming_url.clone(),
+        model: config.streaming_model.clone(),
+        api_key: config.elevenlabs_api_key.clone(),
+        language: config.language.clone(),
+        input_sample_rate,
+        sample_rate: match config.target_sample_rate {
+            0 => input_sample_rate,
+            rate => rate,
+        },
+    };
+
+    let app = app.clone();
+    let session = StreamingSession::start(rt.handle(), options, move |event| {
+        let _ = match event {
+            StreamEvent::Partial(text) => app.emit("transcript-partial", text),
+            StreamEvent::Final(text) => app.emit("transcript-final", text),
+        };
+    });
+
+    // The recording is still uploaded when it stops, so this only costs the live preview
+    session
+        .map_err(|e| {
+            tracing::warn!("failed to start realtime transcription: {}", e);
+            state.add_log("WARN", &format!("failed to start realtime transcription: {}", e));
+        })
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn take_end_inserts_rest() {
+        let rest = Ok(Transcript::from_text("and that's it".to_string()));
+        assert_eq!(take_end(Some(&rest), false), TakeEnd::InsertRest);
+        assert_eq!(take_end(Some(&rest), true), TakeEnd::InsertRest);
+
+        let failed = Err(anyhow::anyhow!("backend down"));
+        assert_eq!(take_end(Some(&failed), true), TakeEnd::InsertRest);
+    }
+
+    #[test]
+    fn continuous_take_without_rest_is_not_empty() {
+        // Every utterance went in as it ended, so stop leaves an empty clip behind
+        assert_eq!(take_end(None, true), TakeEnd::AlreadyInserted);
+
+        let silent = Ok(Transcript::from_text(String::new()));
+        assert_eq!(take_end(Some(&silent), true), TakeEnd::AlreadyInserted);
+    }
+
+    #[test]
+    fn take_without_speech() {
+        assert_eq!(take_end(None, false), TakeEnd::NoSpeech);
+
+        // An empty transcript on its own is still reported as one
+        let silent = Ok(Transcript::from_text(String::new()));
+        assert_eq!(take_end(Some(&silent), false), TakeEnd::InsertRest);
+    }
+}
@@ -35,6 +35,15 @@ impl TextInserter {
 
         Ok(())
     }
+
+    // The Return that insert() would have pressed, for text that already went in without one
+    pub fn press_return(&self) -> Result<()> {
+        if self.auto_enter {
+            simulate_return()?;
+        }
+
+        Ok(())
+    }
 }
 
 #[cfg(target_os = "macos")]
@@ -0,0 +1,445 @@
+use crate::audio::StreamResampler;
+use anyhow::{Context, Result};
+use base64::Engine;
+use futures_util::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::Message;
+
+// How long to wait for the committed transcript after the user stops talking
+const FINAL_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StreamEvent {
+    Partial(String),
+    Final(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct StreamingOptions {
+    pub url: String,
+    pub model: String,
+    pub api_key: String,
+    pub language: String,
+    // Rate the captured audio arrives at
+    pub input_sample_rate: u32,
+    // Rate sent to the server, the same one uploads are resampled to
+    pub sample_rate: u32,
+}
+
+enum Command {
+    Audio(Vec<i16>),
+    Commit,
+}
+
+// Outgoing realtime message (ElevenLabs Scribe realtime protocol)
+#[derive(Serialize)]
+struct AudioChunk<'a> {
+    message_type: &'static str,
+    audio_base_64: &'a str,
+    commit: bool,
+    sample_rate: u32,
+}
+
+#[derive(Debug, Deserialize)]
+struct ServerMessage {
+    message_type: String,
+    #[serde(default)]
+    text: String,
+    #[serde(default)]
+    error: Option<String>,
+}
+
+// A realtime transcription session that receives audio while the user is still speaking
+pub struct StreamingSession {
+    commands: mpsc::UnboundedSender<Command>,
+    resampler: StreamResampler,
+    task: JoinHandle<Result<String>>,
+}
+
+impl StreamingSession {
+    // Connects in the background; audio sent before the socket is open is queued
+    pub fn start<F>(runtime: &tokio::runtime::Handle, options: StreamingOptions, on_event: F) -> Result<Self>
+    where
+        F: Fn(StreamEvent) + Send + 'static,
+    {
+        let resampler = StreamResampler::new(options.input_sample_rate, options.sample_rate)?;
+        let (commands, rx) = mpsc::unbounded_channel();
+        let task = runtime.spawn(run_session(options, rx, on_event));
+
+        Ok(Self {
+            commands,
+            resampler,
+            task,
+        })
+    }
+
+    // Queue mono samples as 16-bit PCM at the session's sample rate
+    pub fn send_audio(&mut self, samples: &[f32]) {
+        match self.resampler.process(samples) {
+            Ok(samples) => self.send_pcm(&samples),
+            Err(e) => tracing::warn!("dropping realtime audio chunk: {}", e),
+        }
+    }
+
+    fn send_pcm(&self, samples: &[f32]) {
+        if samples.is_empty() {
+            return;
+        }
+
+        let pcm = samples
+            .iter()
+            .map(|&s| (s.clamp(-1.0, 1.0) * i16::MAX as f32) as i16)
+            .collect();
+
+        let _ = self.commands.send(Command::Audio(pcm));
+    }
+
+    // Commit the buffered audio and wait for the final transcript
+    pub async fn finish(mut self) -> Result<String> {
+        // The resampler still holds the last few milliseconds
+        match self.resampler.flush() {
+            Ok(samples) => self.send_pcm(&samples),
+            Err(e) => tracing::warn!("dropping end of realtime audio: {}", e),
+        }
+        let _ = self.commands.send(Command::Commit);
+
+        match tokio::time::timeout(FINAL_TIMEOUT, &mut self.task).await {
+            Ok(result) => result.context("streaming task panicked")?,
+            Err(_) => {
+                self.task.abort();
+                anyhow::bail!("timed out waiting for final transcript")
+            }
+        }
+    }
+}
+
+fn session_url(options: &StreamingOptions) -> Result<String> {
+    let mut url = reqwest::Url::parse(&options.url).context("invalid streaming URL")?;
+
+    {
+        let mut query = url.query_pairs_mut();
+        query
+            .append_pair("model_id", &options.model)
+            .append_pair("audio_format", &format!("pcm_{}", options.sample_rate))
+            .append_pair("commit_strategy", "manual");
+        if options.language != "auto" {
+            query.append_pair("language_code", &options.language);
+        }
+    }
+
+    Ok(url.into())
+}
+
+async fn run_session<F>(
+    options: StreamingOptions,
+    mut commands: mpsc::UnboundedReceiver<Command>,
+    on_event: F,
+) -> Result<String>
+where
+    F: Fn(StreamEvent) + Send + 'static,
+{
+    let mut request = session_url(&options)?
+        .into_client_request()
+        .context("invalid streaming URL")?;
+
+    if !options.api_key.is_empty() {
+        request.headers_mut().insert(
+            "xi-api-key",
+            options.api_key.parse().context("invalid API key header")?,
+        );
+    }
+
+    let start = std::time::Instant::now();
+    let (socket, _) = tokio_tungstenite::connect_async(request)
+        .await
+        .context("failed to connect to realtime transcription")?;
+    tracing::info!("realtime transcription connected in {:?}", start.elapsed());
+
+    let (mut sink, mut stream) = socket.split();
+    let mut committed = Vec::new();
+    let mut commit_sent = false;
+    let mut commands_open = true;
+
+    loop {
+        tokio::select! {
+            command = commands.recv(), if commands_open => {
+                let (pcm, commit) = match command {
+                    Some(Command::Audio(pcm)) => (pcm, false),
+                    Some(Command::Commit) => (Vec::new(), true),
+                    // Session dropped without finish(), nothing left to wait for
+                    None => {
+                        commands_open = false;
+                        if !commit_sent {
+                            break;
+                        }
+                        continue;
+                    }
+                };
+
+                let bytes: Vec<u8> = pcm.iter().flat_map(|s| s.to_le_bytes()).collect();
+                let audio = base64::engine::general_purpose::STANDARD.encode(bytes);
+                let chunk = AudioChunk {
+                    message_type: "input_audio_chunk",
+                    audio_base_64: &audio,
+                    commit,
+                    sample_rate: options.sample_rate,
+                };
+
+                sink.send(Message::Text(serde_json::to_string(&chunk)?.into()))
+                    .await
+                    .context("failed to send audio chunk")?;
+
+                if commit {
+                    tracing::debug!("realtime audio committed");
+                    commit_sent = true;
+                }
+            }
+            message = stream.next() => {
+                let text = match message {
+                    Some(Ok(Message::Text(text))) => text,
+                    Some(Ok(Message::Close(_))) | None => break,
+                    Some(Ok(_)) => continue,
+                    Some(Err(e)) => return Err(e).context("realtime transcription connection failed"),
+                };
+
+                let message: ServerMessage = match serde_json::from_str(&text) {
+                    Ok(message) => message,
+                    Err(e) => {
+                        tracing::warn!("unrecognized realtime message: {} ({})", text.as_str(), e);
+                        continue;
+                    }
+                };
+
+                match message.message_type.as_str() {
+                    "session_started" => tracing::debug!("realtime session started"),
+                    "partial_transcript" => on_event(StreamEvent::Partial(message.text)),
+                    "committed_transcript" | "committed_transcript_with_timestamps" => {
+                        if !message.text.is_empty() {
+                            on_event(StreamEvent::Final(message.text.clone()));
+                            committed.push(message.text);
+                        }
+                        if commit_sent {
+                            break;
+                        }
+                    }
+                    other => {
+                        if let Some(error) = message.error {
+                            anyhow::bail!("realtime transcription error ({}): {}", other, error);
+                        }
+                        tracing::debug!("ignoring realtime message: {}", other);
+                    }
+                }
+            }
+        }
+    }
+
+    let _ = sink.send(Message::Close(None)).await;
+
+    let text = committed.join(" ").trim().to_string();
+    tracing::info!("realtime transcription took {:?}", start.elapsed());
+    tracing::info!("raw transcription: {}", text);
+
+    Ok(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use tokio::net::TcpListener;
+    use tokio_tungstenite::tungstenite::handshake::server::{Request, Response};
+
+    fn options(url: String) -> StreamingOptions {
+        StreamingOptions {
+            url,
+            model: "scribe_v2_realtime".to_string(),
+            api_key: "test-key".to_string(),
+            language: "en".to_string(),
+            input_sample_rate: 48_000,
+            sample_rate: 16_000,
+        }
+    }
+
+    // Accepts one connection, sends `greeting`, then answers a commit with `on_commit`.
+    // Returns the request URI and every audio chunk received.
+    // The handshake callback's error type is tungstenite's, not ours to shrink.
+    #[allow(clippy::result_large_err)]
+    async fn mock_server(
+        greeting: Vec<serde_json::Value>,
+        on_commit: Vec<serde_json::Value>,
+    ) -> (String, JoinHandle<(String, Vec<serde_json::Value>)>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!(
+            "ws://{}/v1/speech-to-text/realtime",
+            listener.local_addr().unwrap()
+        );
+
+        let server = tokio::spawn(async move {
+            let (tcp, _) = listener.accept().await.unwrap();
+            let uri = Arc::new(Mutex::new(String::new()));
+            let seen = Arc::clone(&uri);
+            let mut socket =
+                tokio_tungstenite::accept_hdr_async(tcp, move |request: &Request, response: Response| {
+                    *seen.lock().unwrap() = request.uri().to_string();
+                    Ok(response)
+                })
+                .await
+                .unwrap();
+
+            for message in greeting {
+                socket
+                    .send(Message::Text(message.to_string().into()))
+                    .await
+                    .unwrap();
+            }
+
+            let mut chunks = Vec::new();
+            while let Some(Ok(message)) = socket.next().await {
+                let Message::Text(text) = message else { continue };
+                let chunk: serde_json::Value = serde_json::from_str(&text).unwrap();
+                let commit = chunk["commit"] == true;
+                chunks.push(chunk);
+                if commit {
+                    for message in &on_commit {
+                        socket
+                            .send(Message::Text(message.to_string().into()))
+                            .await
+                            .unwrap();
+                    }
+                }
+            }
+
+            let uri = uri.lock().unwrap().clone();
+            (uri, chunks)
+        });
+
+        (url, server)
+    }
+
+    fn message(message_type: &str, text: &str) -> serde_json::Value {
+        serde_json::json!({ "message_type": message_type, "text": text })
+    }
+
+    #[tokio::test]
+    async fn commit_returns_committed_transcripts() {
+        let (url, server) = mock_server(
+            vec![
+                message("session_started", ""),
+                message("partial_transcript", "hello"),
+                // Committed by the server's own segmentation, before our commit
+                message("committed_transcript", "hello there."),
+            ],
+            vec![
+                message("partial_transcript", "general"),
+                message("committed_transcript", "General Kenobi."),
+            ],
+        )
+        .await;
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let recorded = Arc::clone(&events);
+        let mut session =
+            StreamingSession::start(&tokio::runtime::Handle::current(), options(url), move |event| {
+                recorded.lock().unwrap().push(event)
+            })
+            .unwrap();
+
+        // 0.5 s at 48 kHz in uneven pieces, like the input loop sends it
+        for len in [4800, 7000, 12_200] {
+            session.send_audio(&vec![0.1; len]);
+        }
+        // Let the server's own commit arrive first; the session ends at the first one after ours
+        while events.lock().unwrap().len() < 2 {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        let text = session.finish().await.unwrap();
+        assert_eq!(text, "hello there. General Kenobi.");
+
+        let (uri, chunks) = server.await.unwrap();
+        assert!(uri.contains("model_id=scribe_v2_realtime"), "{}", uri);
+        assert!(uri.contains("audio_format=pcm_16000"), "{}", uri);
+        assert!(uri.contains("commit_strategy=manual"), "{}", uri);
+        assert!(uri.contains("language_code=en"), "{}", uri);
+
+        // Only the last chunk commits, and everything arrives resampled to 16 kHz
+        let (last, audio) = chunks.split_last().unwrap();
+        assert_eq!(last["commit"], true);
+        assert!(audio.iter().all(|chunk| chunk["commit"] == false));
+        assert!(chunks.iter().all(|chunk| chunk["sample_rate"] == 16_000));
+        let bytes: usize = chunks
+            .iter()
+            .map(|chunk| {
+                let audio = chunk["audio_base_64"].as_str().unwrap();
+                base64::engine::general_purpose::STANDARD
+                    .decode(audio)
+                    .unwrap()
+                    .len()
+            })
+            .sum();
+        assert_eq!(bytes / 2, 8000);
+
+        let events = events.lock().unwrap();
+        assert_eq!(
+            *events,
+            vec![
+                StreamEvent::Partial("hello".to_string()),
+                StreamEvent::Final("hello there.".to_string()),
+                StreamEvent::Partial("general".to_string()),
+                StreamEvent::Final("General Kenobi.".to_string()),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn server_error_fails_the_session() {
+        let (url, _server) = mock_server(
+            vec![serde_json::json!({
+                "message_type": "auth_error",
+                "error": "invalid api key",
+            })],
+            Vec::new(),
+        )
+        .await;
+
+        let session =
+            StreamingSession::start(&tokio::runtime::Handle::current(), options(url), |_| {}).unwrap();
+        let e = session.finish().await.unwrap_err();
+        assert!(e.to_string().contains("invalid api key"), "{:#}", e);
+    }
+
+    #[tokio::test]
+    async fn empty_commit_returns_empty_text() {
+        let (url, _server) = mock_server(Vec::new(), vec![message("committed_transcript", "")]).await;
+
+        let session =
+            StreamingSession::start(&tokio::runtime::Handle::current(), options(url), |_| {}).unwrap();
+        assert_eq!(session.finish().await.unwrap(), "");
+    }
+
+    #[test]
+    fn session_url_escapes_query_values() {
+        let mut options = options("wss://example.com/realtime?token=abc".to_string());
+        options.model = "scribe v2&x=1".to_string();
+        options.language = "pt-BR#".to_string();
+
+        let url = session_url(&options).unwrap();
+        assert_eq!(
+            url,
+            "wss://example.com/realtime?token=abc&model_id=scribe+v2%26x%3D1\
+             &audio_format=pcm_16000&commit_strategy=manual&language_code=pt-BR%23"
+        );
+    }
+
+    #[test]
+    fn session_url_omits_auto_language() {
+        let mut options = options("wss://example.com/realtime".to_string());
+        options.language = "auto".to_string();
+
+        let url = session_url(&options).unwrap();
+        assert!(!url.contains("language_code"), "{}", url);
+    }
+}
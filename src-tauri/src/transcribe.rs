@@ -1,84 +1,1232 @@
+use crate::audio::AudioClip;
+use crate::config::{AudioFormat, Config, TimestampGranularity, TranscriptionBackend};
 use anyhow::{Context, Result};
+use async_trait::async_trait;
+use futures_util::{StreamExt, TryStreamExt};
 use reqwest::multipart;
-use serde::Deserialize;
-use serde_json;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::hash::{BuildHasher, Hasher};
 use std::time::Duration;
 
+// Speech-to-text backend, implemented once per provider
+#[async_trait]
+pub trait Transcriber: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    // Each backend encodes the clip in whatever format it uploads
+    async fn transcribe(&self, clip: &AudioClip) -> Result<Transcript>;
+
+    // Remote backends get long recordings in chunks to stay under timeouts and size limits
+    fn needs_chunking(&self) -> bool {
+        true
+    }
+}
+
+// A recognized word with its position in the recording, in seconds
+#[derive(Debug, Clone, Serialize)]
+pub struct Word {
+    pub text: String,
+    pub start: f32,
+    pub end: f32,
+    // 0.0-1.0, None when the backend doesn't report it
+    pub confidence: Option<f32>,
+}
+
+// A stretch of speech by one speaker, from a diarized transcription
+#[derive(Debug, Clone, Serialize)]
+pub struct Segment {
+    pub speaker: String,
+    pub text: String,
+    pub start: f32,
+    pub end: f32,
+}
+
+// Transcription result with whatever detail the backend provides beyond the text
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Transcript {
+    pub text: String,
+    pub language_code: Option<String>,
+    pub language_probability: Option<f32>,
+    pub words: Vec<Word>,
+    // Only filled in when diarization was requested
+    pub segments: Vec<Segment>,
+}
+
+impl Transcript {
+    pub fn from_text(text: String) -> Self {
+        Self {
+            text,
+            ..Default::default()
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.text.trim().is_empty()
+    }
+
+    // Backends report language names or ISO 639-1/639-3 codes
+    pub fn is_english(&self, min_probability: f32) -> bool {
+        let english = matches!(
+            self.language_code.as_deref().map(str::to_lowercase).as_deref(),
+            Some("en" | "eng" | "english")
+        );
+        english && self.language_probability.is_none_or(|p| p >= min_probability)
+    }
+
+    pub fn low_confidence_words(&self, threshold: f32) -> impl Iterator<Item = &Word> {
+        self.words
+            .iter()
+            .filter(move |word| word.confidence.is_some_and(|c| c < threshold))
+    }
+
+    // The text with a paragraph break wherever the speaker paused for at least `pause_secs`
+    pub fn paragraphs(&self, pause_secs: f32) -> String {
+        let mut text = String::with_capacity(self.text.len());
+        let mut cursor = 0;
+        let mut last_end: Option<f32> = None;
+
+        // Words are located in the text rather than joined, so spacing and punctuation survive
+        for word in self.words.iter().filter(|word| !word.text.is_empty()) {
+            let Some(pos) = self.text[cursor..].find(&word.text).map(|pos| cursor + pos) else {
+                continue;
+            };
+            if last_end.is_some_and(|end| word.start - end >= pause_secs) {
+                text.push_str(self.text[cursor..pos].trim_end());
+                text.push_str("\n\n");
+            } else {
+                text.push_str(&self.text[cursor..pos]);
+            }
+            text.push_str(&word.text);
+            cursor = pos + word.text.len();
+            last_end = Some(word.end);
+        }
+
+        text.push_str(&self.text[cursor..]);
+        text
+    }
+
+    // "Speaker 1: ..." paragraphs, or None unless more than one person spoke
+    pub fn speaker_text(&self) -> Option<String> {
+        let first = self.segments.first()?;
+        if self.segments.iter().all(|segment| segment.speaker == first.speaker) {
+            return None;
+        }
+
+        let paragraphs: Vec<String> = self
+            .segments
+            .iter()
+            .map(|segment| format!("{}: {}", segment.speaker, segment.text))
+            .collect();
+        Some(paragraphs.join("\n\n"))
+    }
+
+    fn offset(mut self, secs: f32) -> Self {
+        for word in &mut self.words {
+            word.start += secs;
+            word.end += secs;
+        }
+        for segment in &mut self.segments {
+            segment.start += secs;
+            segment.end += secs;
+        }
+        self
+    }
+}
+
+// Most chunks of one recording in flight at once
+const MAX_PARALLEL_CHUNKS: usize = 4;
+// Longest run of words a chunk may repeat from the end of the previous one
+const MAX_OVERLAP_WORDS: usize = 8;
+
+// Transcribe a recording of any length, splitting it at silence above `chunk_secs`
+pub async fn transcribe_long(
+    transcriber: &dyn Transcriber,
+    clip: &AudioClip,
+    chunk_secs: u32,
+) -> Result<Transcript> {
+    if chunk_secs == 0 || !transcriber.needs_chunking() {
+        return transcriber.transcribe(clip).await;
+    }
+
+    let chunks = clip.split_at_silence(chunk_secs);
+    if chunks.len() == 1 {
+        return transcriber.transcribe(clip).await;
+    }
+
+    // buffered() keeps results in chunk order
+    let count = chunks.len();
+    let parts: Vec<Transcript> = futures_util::stream::iter(chunks.iter().enumerate())
+        .map(|(i, (offset, chunk))| async move {
+            transcriber
+                .transcribe(chunk)
+                .await
+                .map(|part| part.offset(*offset))
+                .with_context(|| format!("chunk {} of {} failed", i + 1, count))
+        })
+        .buffered(MAX_PARALLEL_CHUNKS)
+        .try_collect()
+        .await?;
+
+    let transcript = stitch(parts);
+    tracing::info!("stitched transcription: {}", transcript.text);
+
+    Ok(transcript)
+}
+
+// Join chunk transcripts, dropping what was heard twice because of the chunk overlap
+fn stitch(parts: Vec<Transcript>) -> Transcript {
+    let texts: Vec<&str> = parts.iter().map(|part| part.text.as_str()).collect();
+    let text = stitch_text(&texts);
+
+    // Words carry timings, so overlap is anything starting before the previous chunk ended
+    let mut words: Vec<Word> = Vec::new();
+    for part in &parts {
+        let last_end = words.last().map(|word| word.end);
+        words.extend(
+            part.words
+                .iter()
+                .filter(|word| last_end.is_none_or(|end| word.start >= end))
+                .cloned(),
+        );
+    }
+
+    // Segments are too long to drop whole, so only the repeated words at the seam go
+    let mut segments: Vec<Segment> = Vec::new();
+    for part in &parts {
+        let last = segments.last().map(|segment| (segment.end, segment.text.clone()));
+        for segment in &part.segments {
+            let mut segment = segment.clone();
+            if let Some((last_end, last_text)) = &last {
+                if segment.end <= *last_end {
+                    continue;
+                }
+                if segment.start < *last_end {
+                    let next: Vec<&str> = segment.text.split_whitespace().collect();
+                    let previous: Vec<&str> = last_text.split_whitespace().collect();
+                    segment.text = next[overlap_words(&previous, &next)..].join(" ");
+                    segment.start = *last_end;
+                }
+            }
+            segments.push(segment);
+        }
+    }
+
+    // Language of the chunk with the most speech
+    let main = parts.iter().max_by_key(|part| part.text.len());
+
+    Transcript {
+        text,
+        language_code: main.and_then(|part| part.language_code.clone()),
+        language_probability: main.and_then(|part| part.language_probability),
+        words,
+        segments,
+    }
+}
+
+// Join chunk texts, dropping words repeated because of the chunk overlap
+fn stitch_text(parts: &[&str]) -> String {
+    let mut words: Vec<&str> = Vec::new();
+
+    for part in parts {
+        let next: Vec<&str> = part.split_whitespace().collect();
+        let overlap = overlap_words(&words, &next);
+        if overlap > 0 {
+            tracing::debug!("dropping {} overlapping words", overlap);
+        }
+        words.extend(&next[overlap..]);
+    }
+
+    words.join(" ")
+}
+
+// Number of leading words in `next` that repeat the end of `previous`
+fn overlap_words(previous: &[&str], next: &[&str]) -> usize {
+    let max = MAX_OVERLAP_WORDS.min(previous.len()).min(next.len());
+    (1..=max)
+        .rev()
+        .find(|&n| {
+            previous[previous.len() - n..]
+                .iter()
+                .zip(&next[..n])
+                .all(|(a, b)| normalize_word(a) == normalize_word(b))
+        })
+        .unwrap_or(0)
+}
+
+// Compare words ignoring case and punctuation ("Hello," == "hello")
+fn normalize_word(word: &str) -> String {
+    word.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+// Attempts per request, including the first
+const MAX_ATTEMPTS: u32 = 4;
+// Backoff before the first retry; doubles with every attempt
+const BASE_BACKOFF: Duration = Duration::from_millis(500);
+const MAX_BACKOFF: Duration = Duration::from_secs(8);
+// A provider asking us to wait longer than this fails right away instead of stalling dictation
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(10);
+// Per-request time limit for a clip of no length
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+// Extra time per second of audio in the request
+const TIMEOUT_PER_AUDIO_SEC: f32 = 1.0;
+// Longest slice of an unexpected response body kept in error messages
+const MAX_ERROR_BODY: usize = 200;
+
+// Why a transcription request failed, classified from the HTTP status or transport error
+#[derive(Debug)]
+pub enum TranscribeError {
+    Unauthorized(u16),
+    RateLimited { retry_after: Option<Duration> },
+    Server(u16),
+    Timeout,
+    PayloadTooLarge,
+    // Any other 4xx, e.g. an unknown model or language
+    Rejected { status: u16, message: String },
+    Connection(String),
+}
+
+impl TranscribeError {
+    async fn from_response(response: reqwest::Response) -> Self {
+        let status = response.status().as_u16();
+        let retry_after = response
+            .headers()
+            .get(reqwest::header::RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(parse_retry_after);
+        let body = response.text().await.unwrap_or_default();
+        tracing::debug!("error response ({}): {}", status, body);
+
+        match status {
+            401 | 403 => Self::Unauthorized(status),
+            408 => Self::Timeout,
+            413 => Self::PayloadTooLarge,
+            429 => Self::RateLimited { retry_after },
+            500.. => Self::Server(status),
+            _ => Self::Rejected {
+                status,
+                message: error_message(&body),
+            },
+        }
+    }
+
+    fn from_reqwest(err: reqwest::Error) -> Self {
+        if err.is_timeout() {
+            Self::Timeout
+        } else {
+            Self::Connection(err.to_string())
+        }
+    }
+
+    // Whether the same request may succeed if sent again
+    pub fn is_transient(&self) -> bool {
+        matches!(
+            self,
+            Self::RateLimited { .. } | Self::Server(_) | Self::Timeout | Self::Connection(_)
+        )
+    }
+
+    // What the user can do about it, shown instead of the raw response body
+    pub fn hint(&self) -> &'static str {
+        match self {
+            Self::Unauthorized(_) => "check the API key in settings",
+            Self::RateLimited { .. } => "the provider is throttling requests, wait a moment and try again",
+            Self::Server(_) => "the provider is having problems, try again later or switch backend",
+            Self::Timeout => "check your connection, or use shorter chunks for long recordings",
+            Self::PayloadTooLarge => "use shorter chunks or a compressed upload format",
+            Self::Rejected { .. } => "check the model and language settings",
+            Self::Connection(_) => "check your network connection and the server URL",
+        }
+    }
+
+    // Wait before attempt `attempt + 1`, or None when the request should not be retried
+    fn retry_delay(&self, attempt: u32) -> Option<Duration> {
+        if !self.is_transient() || attempt >= MAX_ATTEMPTS {
+            return None;
+        }
+        match self {
+            Self::RateLimited {
+                retry_after: Some(retry_after),
+            } => (*retry_after <= MAX_RETRY_AFTER).then_some(*retry_after),
+            _ => Some(backoff(attempt)),
+        }
+    }
+}
+
+impl fmt::Display for TranscribeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unauthorized(status) => write!(f, "API key rejected ({})", status),
+            Self::RateLimited {
+                retry_after: Some(retry_after),
+            } => write!(f, "rate limited, retry after {}s", retry_after.as_secs()),
+            Self::RateLimited { retry_after: None } => write!(f, "rate limited"),
+            Self::Server(status) => write!(f, "server error ({})", status),
+            Self::Timeout => write!(f, "request timed out"),
+            Self::PayloadTooLarge => write!(f, "recording too large for the provider"),
+            Self::Rejected { status, message } => write!(f, "request rejected ({}): {}", status, message),
+            Self::Connection(err) => write!(f, "could not reach server: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for TranscribeError {}
+
+// Retry-After is either delay-seconds or an HTTP date
+fn parse_retry_after(value: &str) -> Option<Duration> {
+    if let Ok(secs) = value.trim().parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let date = chrono::DateTime::parse_from_rfc2822(value.trim()).ok()?;
+    (date.with_timezone(&chrono::Utc) - chrono::Utc::now()).to_std().ok()
+}
+
+// Pull the human-readable part out of an error body from ElevenLabs or OpenAI-style servers
+fn error_message(body: &str) -> String {
+    let json: Option<serde_json::Value> = serde_json::from_str(body).ok();
+    let message = json.as_ref().and_then(|json| {
+        [
+            json.pointer("/detail/message"),
+            json.pointer("/detail"),
+            json.pointer("/error/message"),
+            json.pointer("/error"),
+            json.pointer("/message"),
+        ]
+        .into_iter()
+        .flatten()
+        .find_map(|value| value.as_str())
+    });
+
+    let message = message.unwrap_or(body).trim();
+    match message.char_indices().nth(MAX_ERROR_BODY) {
+        Some((end, _)) => format!("{}...", &message[..end]),
+        None => message.to_string(),
+    }
+}
+
+// Exponential backoff with jitter, so clients that failed together don't retry together
+fn backoff(attempt: u32) -> Duration {
+    let max = BASE_BACKOFF
+        .saturating_mul(1 << (attempt - 1).min(16))
+        .min(MAX_BACKOFF);
+    // RandomState is seeded per instance, which is plenty of randomness for jitter
+    let random = std::collections::hash_map::RandomState::new().build_hasher().finish();
+    let jitter = (random % 1000) as f64 / 1000.0;
+    max.mul_f64(0.5 + jitter / 2.0)
+}
+
+// Upload and processing time scale with the audio, so long chunks get longer to finish
+fn request_timeout(clip: &AudioClip) -> Duration {
+    REQUEST_TIMEOUT + Duration::from_secs_f32(clip.duration_secs() * TIMEOUT_PER_AUDIO_SEC)
+}
+
+// Send a request, retrying transient failures; `build` is called again for every attempt
+async fn send_with_retry<F>(provider: &str, build: F) -> Result<reqwest::Response>
+where
+    F: Fn() -> Result<reqwest::RequestBuilder>,
+{
+    let mut attempt = 1;
+    loop {
+        let error = match build()?.send().await {
+            Ok(response) if response.status().is_success() => return Ok(response),
+            Ok(response) => TranscribeError::from_response(response).await,
+            Err(e) => TranscribeError::from_reqwest(e),
+        };
+
+        let Some(delay) = error.retry_delay(attempt) else {
+            return Err(anyhow::Error::new(error).context(format!("{} request failed", provider)));
+        };
+        tracing::warn!(
+            "{} request failed ({}), retrying in {:?} (attempt {} of {})",
+            provider,
+            error,
+            delay,
+            attempt + 1,
+            MAX_ATTEMPTS
+        );
+        tokio::time::sleep(delay).await;
+        attempt += 1;
+    }
+}
+
+pub fn create_transcriber(config: &Config, backend: TranscriptionBackend) -> Result<Box<dyn Transcriber>> {
+    let transcriber: Box<dyn Transcriber> = match backend {
+        TranscriptionBackend::ElevenLabs => Box::new(ElevenLabsClient::new(
+            config.elevenlabs_base_url.clone(),
+            config.elevenlabs_api_key.clone(),
+            config.language.clone(),
+            config.elevenlabs_format,
+            ScribeOptions::from(config),
+        )),
+        TranscriptionBackend::OpenAi => Box::new(OpenAiClient::new(
+            config.openai_base_url.clone(),
+            config.openai_model.clone(),
+            config.openai_api_key.clone(),
+            config.language.clone(),
+            config.openai_format,
+            config.openai_word_timestamps,
+        )),
+        #[cfg(feature = "local-whisper")]
+        TranscriptionBackend::Local => Box::new(crate::whisper::LocalWhisper::new(
+            &config.whisper_model_path,
+            config.language.clone(),
+        )?),
+        #[cfg(not(feature = "local-whisper"))]
+        TranscriptionBackend::Local => {
+            anyhow::bail!("local transcription requires building with the local-whisper feature")
+        }
+    };
+
+    Ok(transcriber)
+}
+
 #[derive(Debug, Deserialize)]
-struct TranscriptionResponse {
+struct ElevenLabsResponse {
     text: String,
+    language_code: Option<String>,
+    language_probability: Option<f32>,
+    #[serde(default)]
+    words: Vec<ElevenLabsWord>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ElevenLabsWord {
+    text: String,
+    start: Option<f32>,
+    end: Option<f32>,
+    // "word", "spacing" or "audio_event"
+    #[serde(rename = "type")]
+    kind: String,
+    logprob: Option<f32>,
+    // e.g. "speaker_0", only present when diarizing
+    speaker_id: Option<String>,
+}
+
+impl From<ElevenLabsResponse> for Transcript {
+    fn from(response: ElevenLabsResponse) -> Self {
+        let segments = speaker_segments(&response.words);
+
+        let words = response
+            .words
+            .into_iter()
+            .filter(|word| word.kind == "word")
+            .filter_map(|word| {
+                Some(Word {
+                    text: word.text,
+                    // Missing when timestamps_granularity is "none"
+                    start: word.start?,
+                    end: word.end?,
+                    confidence: word.logprob.map(f32::exp),
+                })
+            })
+            .collect();
+
+        Self {
+            text: response.text,
+            language_code: response.language_code,
+            language_probability: response.language_probability,
+            words,
+            segments,
+        }
+    }
+}
+
+// Group consecutive entries by speaker; spacing entries are kept so the text reads naturally
+fn speaker_segments(words: &[ElevenLabsWord]) -> Vec<Segment> {
+    let mut segments: Vec<Segment> = Vec::new();
+
+    for word in words {
+        let Some(speaker_id) = &word.speaker_id else {
+            continue;
+        };
+        let speaker = speaker_label(speaker_id);
+        let start = word.start.unwrap_or_default();
+        let end = word.end.unwrap_or(start);
+
+        match segments.last_mut() {
+            Some(segment) if segment.speaker == speaker => {
+                segment.text.push_str(&word.text);
+                segment.end = segment.end.max(end);
+            }
+            // A new speaker never starts with the space before their first word
+            _ if word.kind == "spacing" => {}
+            _ => segments.push(Segment {
+                speaker,
+                text: word.text.clone(),
+                start,
+                end,
+            }),
+        }
+    }
+
+    for segment in &mut segments {
+        segment.text = segment.text.trim().to_string();
+    }
+    segments
+}
+
+// "speaker_0" -> "Speaker 1"
+fn speaker_label(speaker_id: &str) -> String {
+    match speaker_id
+        .strip_prefix("speaker_")
+        .and_then(|n| n.parse::<u32>().ok())
+    {
+        Some(n) => format!("Speaker {}", n + 1),
+        None => speaker_id.to_string(),
+    }
+}
+
+// Scribe request options beyond the language
+#[derive(Debug, Clone)]
+pub struct ScribeOptions {
+    pub model_id: String,
+    pub diarize: bool,
+    pub num_speakers: Option<u32>,
+    pub tag_audio_events: bool,
+    pub timestamps: TimestampGranularity,
+}
+
+impl From<&Config> for ScribeOptions {
+    fn from(config: &Config) -> Self {
+        Self {
+            model_id: config.elevenlabs_model_id.clone(),
+            diarize: config.elevenlabs_diarize,
+            num_speakers: config.elevenlabs_num_speakers,
+            tag_audio_events: config.elevenlabs_tag_audio_events,
+            timestamps: config.elevenlabs_timestamps,
+        }
+    }
 }
 
 pub struct ElevenLabsClient {
+    base_url: String,
     api_key: String,
     language: String,
+    format: AudioFormat,
+    options: ScribeOptions,
     client: reqwest::Client,
 }
 
 impl ElevenLabsClient {
-    pub fn new(api_key: String, language: String) -> Self {
+    pub fn new(
+        base_url: String,
+        api_key: String,
+        language: String,
+        format: AudioFormat,
+        options: ScribeOptions,
+    ) -> Self {
         let client = reqwest::Client::builder()
-            .timeout(Duration::from_secs(30))
             .build()
             .expect("failed to build HTTP client");
 
         Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
             api_key,
             language,
+            format,
+            options,
             client,
         }
     }
+}
+
+#[async_trait]
+impl Transcriber for ElevenLabsClient {
+    fn name(&self) -> &'static str {
+        "ElevenLabs"
+    }
 
-    pub async fn transcribe(&self, audio_data: Vec<u8>) -> Result<String> {
-        if audio_data.is_empty() {
-            return Ok(String::new());
+    // Speakers are labelled per request, so "speaker_0" in one chunk needn't be "speaker_0" in
+    // the next; a diarized recording is sent whole to keep the labels consistent
+    fn needs_chunking(&self) -> bool {
+        !self.options.diarize
+    }
+
+    async fn transcribe(&self, clip: &AudioClip) -> Result<Transcript> {
+        if clip.is_empty() {
+            return Ok(Transcript::default());
         }
 
+        let audio = clip.encode(self.format)?;
         tracing::info!(
-            "sending {} bytes to ElevenLabs (language: {})",
-            audio_data.len(),
-            self.language
+            "sending {} bytes of {} to ElevenLabs (model: {}, language: {}, diarize: {})",
+            audio.data.len(),
+            audio.mime_type,
+            self.options.model_id,
+            self.language,
+            self.options.diarize
         );
 
         let start = std::time::Instant::now();
-        let mut form = multipart::Form::new()
-            .text("model_id", "scribe_v1")
-            .part(
-                "file",
-                multipart::Part::bytes(audio_data)
-                    .file_name("audio.wav")
-                    .mime_str("audio/wav")?,
-            );
+        let url = format!("{}/speech-to-text", self.base_url);
+        let timeout = request_timeout(clip);
+        let response = send_with_retry(self.name(), || {
+            let options = &self.options;
+            let mut form = multipart::Form::new()
+                .text("model_id", options.model_id.clone())
+                .text("diarize", options.diarize.to_string())
+                .text("tag_audio_events", options.tag_audio_events.to_string())
+                .text("timestamps_granularity", options.timestamps.as_str())
+                .part(
+                    "file",
+                    multipart::Part::bytes(audio.data.clone())
+                        .file_name(audio.file_name)
+                        .mime_str(audio.mime_type)?,
+                );
 
-        if self.language != "auto" {
-            form = form.text("language_code", self.language.clone());
-        }
+            if self.language != "auto" {
+                form = form.text("language_code", self.language.clone());
+            }
+            if let Some(num_speakers) = options.num_speakers.filter(|_| options.diarize) {
+                form = form.text("num_speakers", num_speakers.to_string());
+            }
 
-        tracing::debug!("sending HTTP request...");
-        let response = self
-            .client
-            .post("https://api.elevenlabs.io/v1/speech-to-text")
-            .header("xi-api-key", &self.api_key)
-            .multipart(form)
-            .send()
-            .await
-            .context("failed to send request to ElevenLabs")?;
+            Ok(self
+                .client
+                .post(&url)
+                .timeout(timeout)
+                .header("xi-api-key", &self.api_key)
+                .multipart(form))
+        })
+        .await?;
         tracing::debug!("HTTP response received");
 
-        let status = response.status();
-        if !status.is_success() {
-            let error_text = response.text().await.unwrap_or_default();
-            anyhow::bail!("ElevenLabs API error ({}): {}", status, error_text);
+        let body = response.text().await.context("failed to read response body")?;
+
+        let result: ElevenLabsResponse = serde_json::from_str(&body)
+            .context("failed to parse ElevenLabs response")?;
+
+        tracing::info!("transcription took {:?}", start.elapsed());
+        tracing::info!("raw transcription: {}", result.text);
+
+        Ok(result.into())
+    }
+}
+
+// Client for self-hosted servers speaking the OpenAI transcription API
+// (faster-whisper-server, LocalAI, vLLM, ...)
+pub struct OpenAiClient {
+    base_url: String,
+    model: String,
+    api_key: String,
+    language: String,
+    format: AudioFormat,
+    word_timestamps: bool,
+    client: reqwest::Client,
+}
+
+// `json` only has the text; `verbose_json` adds the language and, when asked for, word timings
+#[derive(Debug, Deserialize)]
+struct OpenAiResponse {
+    text: String,
+    language: Option<String>,
+    #[serde(default)]
+    words: Vec<OpenAiWord>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAiWord {
+    word: String,
+    start: f32,
+    end: f32,
+}
+
+impl From<OpenAiResponse> for Transcript {
+    fn from(response: OpenAiResponse) -> Self {
+        let words = response
+            .words
+            .into_iter()
+            .map(|word| Word {
+                text: word.word.trim().to_string(),
+                start: word.start,
+                end: word.end,
+                confidence: None,
+            })
+            .collect();
+
+        Self {
+            text: response.text.trim().to_string(),
+            language_code: response.language,
+            language_probability: None,
+            words,
+            segments: Vec::new(),
+        }
+    }
+}
+
+impl OpenAiClient {
+    pub fn new(
+        base_url: String,
+        model: String,
+        api_key: String,
+        language: String,
+        format: AudioFormat,
+        word_timestamps: bool,
+    ) -> Self {
+        let client = reqwest::Client::builder()
+            .build()
+            .expect("failed to build HTTP client");
+
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            model,
+            api_key,
+            language,
+            format,
+            word_timestamps,
+            client,
+        }
+    }
+}
+
+#[async_trait]
+impl Transcriber for OpenAiClient {
+    fn name(&self) -> &'static str {
+        "OpenAI-compatible"
+    }
+
+    async fn transcribe(&self, clip: &AudioClip) -> Result<Transcript> {
+        if clip.is_empty() {
+            return Ok(Transcript::default());
         }
 
+        let audio = clip.encode(self.format)?;
+        tracing::info!(
+            "sending {} bytes of {} to {} (model: {}, language: {})",
+            audio.data.len(),
+            audio.mime_type,
+            self.base_url,
+            self.model,
+            self.language
+        );
+
+        let start = std::time::Instant::now();
+        let url = format!("{}/audio/transcriptions", self.base_url);
+        let timeout = request_timeout(clip);
+        let response = send_with_retry(self.name(), || {
+            let mut form = multipart::Form::new()
+                .text("model", self.model.clone())
+                .part(
+                    "file",
+                    multipart::Part::bytes(audio.data.clone())
+                        .file_name(audio.file_name)
+                        .mime_str(audio.mime_type)?,
+                );
+
+            // Not every server supports verbose_json, so word timings are opt-in
+            if self.word_timestamps {
+                form = form
+                    .text("response_format", "verbose_json")
+                    .text("timestamp_granularities[]", "word");
+            } else {
+                form = form.text("response_format", "json");
+            }
+
+            if self.language != "auto" {
+                form = form.text("language", self.language.clone());
+            }
+
+            let mut request = self.client.post(&url).timeout(timeout).multipart(form);
+            if !self.api_key.is_empty() {
+                request = request.bearer_auth(&self.api_key);
+            }
+            Ok(request)
+        })
+        .await?;
+
         let body = response.text().await.context("failed to read response body")?;
 
-        let result: TranscriptionResponse = serde_json::from_str(&body)
-            .context("failed to parse ElevenLabs response")?;
+        let result: OpenAiResponse =
+            serde_json::from_str(&body).context("failed to parse transcription response")?;
 
         tracing::info!("transcription took {:?}", start.elapsed());
         tracing::info!("raw transcription: {}", result.text);
 
-        Ok(result.text)
+        Ok(result.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    fn word(text: &str, start: f32, end: f32) -> Word {
+        Word {
+            text: text.to_string(),
+            start,
+            end,
+            confidence: None,
+        }
+    }
+
+    fn segment(speaker: &str, text: &str, start: f32, end: f32) -> Segment {
+        Segment {
+            speaker: speaker.to_string(),
+            text: text.to_string(),
+            start,
+            end,
+        }
+    }
+
+    #[test]
+    fn overlap_words_ignores_case_and_punctuation() {
+        assert_eq!(
+            overlap_words(&["so", "Hello,", "there"], &["hello", "there.", "friend"]),
+            2
+        );
+        assert_eq!(overlap_words(&["so", "hello"], &["goodbye", "now"]), 0);
+        assert_eq!(overlap_words(&[], &["hello"]), 0);
+    }
+
+    #[test]
+    fn overlap_words_is_capped() {
+        let previous = vec!["la"; 20];
+        let next = vec!["la"; 20];
+        assert_eq!(overlap_words(&previous, &next), MAX_OVERLAP_WORDS);
+    }
+
+    #[test]
+    fn stitch_text_drops_repeats_at_joins() {
+        let text = stitch_text(&[
+            "the quick brown fox",
+            "brown fox jumps over",
+            "Over the lazy dog.",
+        ]);
+        assert_eq!(text, "the quick brown fox jumps over the lazy dog.");
+    }
+
+    #[test]
+    fn stitch_text_keeps_parts_without_overlap() {
+        assert_eq!(stitch_text(&["one two", "", "three four"]), "one two three four");
+    }
+
+    #[test]
+    fn stitch_dedups_words_and_trims_segments_by_time() {
+        let first = Transcript {
+            text: "hello there general".to_string(),
+            words: vec![
+                word("hello", 0.0, 0.5),
+                word("there", 0.6, 1.0),
+                word("general", 1.2, 1.8),
+            ],
+            segments: vec![segment("speaker_0", "hello there general", 0.0, 1.8)],
+            ..Default::default()
+        };
+        // Chunk offsets already applied; it starts one overlap before the first one ended
+        let second = Transcript {
+            text: "general kenobi".to_string(),
+            words: vec![word("general", 1.3, 1.8), word("kenobi", 2.0, 2.6)],
+            segments: vec![segment("speaker_0", "general kenobi", 1.3, 2.6)],
+            ..Default::default()
+        };
+
+        let stitched = stitch(vec![first, second]);
+        assert_eq!(stitched.text, "hello there general kenobi");
+
+        let words: Vec<(&str, f32)> = stitched
+            .words
+            .iter()
+            .map(|w| (w.text.as_str(), w.start))
+            .collect();
+        assert_eq!(
+            words,
+            vec![("hello", 0.0), ("there", 0.6), ("general", 1.2), ("kenobi", 2.0)]
+        );
+
+        assert_eq!(stitched.segments.len(), 2);
+        assert_eq!(stitched.segments[1].text, "kenobi");
+        assert_eq!(stitched.segments[1].start, 1.8);
+    }
+
+    // Reports one word per whole second of audio, named after the level of that second,
+    // which the test clip sets to the absolute second index
+    struct SecondsTranscriber;
+
+    #[async_trait]
+    impl Transcriber for SecondsTranscriber {
+        fn name(&self) -> &'static str {
+            "test"
+        }
+
+        async fn transcribe(&self, clip: &AudioClip) -> Result<Transcript> {
+            let rate = clip.sample_rate as usize;
+            let words: Vec<Word> = (0..clip.samples.len() / rate)
+                .map(|k| {
+                    let second = ((clip.samples[k * rate].abs() - 0.1) / 0.01).round();
+                    word(&format!("s{}", second), k as f32 + 0.1, k as f32 + 0.6)
+                })
+                .collect();
+            let text = words
+                .iter()
+                .map(|w| w.text.as_str())
+                .collect::<Vec<_>>()
+                .join(" ");
+            Ok(Transcript {
+                text,
+                words,
+                ..Default::default()
+            })
+        }
+    }
+
+    fn numbered_seconds(secs: usize) -> AudioClip {
+        let samples = (0..secs)
+            .flat_map(|second| {
+                let level = 0.1 + second as f32 * 0.01;
+                [level, -level].repeat(500)
+            })
+            .collect();
+        AudioClip {
+            samples,
+            sample_rate: 1000,
+        }
+    }
+
+    #[tokio::test]
+    async fn transcribe_long_offsets_chunk_timestamps() {
+        // No pause anywhere, so chunks are cut hard at 0-3 s, 2-5 s and 4-7 s
+        let transcript = transcribe_long(&SecondsTranscriber, &numbered_seconds(7), 3)
+            .await
+            .unwrap();
+
+        assert_eq!(transcript.text, "s0 s1 s2 s3 s4 s5 s6");
+        let words: Vec<(&str, f32)> = transcript
+            .words
+            .iter()
+            .map(|w| (w.text.as_str(), w.start))
+            .collect();
+        let expected: Vec<(String, f32)> = (0..7).map(|k| (format!("s{}", k), k as f32 + 0.1)).collect();
+        assert_eq!(words.len(), expected.len());
+        for ((text, start), (expected_text, expected_start)) in words.iter().zip(&expected) {
+            assert_eq!(text, expected_text);
+            assert!((start - expected_start).abs() < 1e-4, "{} at {}", text, start);
+        }
+    }
+
+    #[tokio::test]
+    async fn transcribe_long_sends_short_clip_whole() {
+        let transcript = transcribe_long(&SecondsTranscriber, &numbered_seconds(2), 3)
+            .await
+            .unwrap();
+        assert_eq!(transcript.text, "s0 s1");
+        assert_eq!(transcript.words[1].start, 1.1);
+    }
+
+    #[test]
+    fn diarized_recordings_are_not_chunked() {
+        let config = Config {
+            elevenlabs_diarize: true,
+            ..Default::default()
+        };
+        let transcriber = create_transcriber(&config, TranscriptionBackend::ElevenLabs).unwrap();
+        assert!(!transcriber.needs_chunking());
+
+        let transcriber = create_transcriber(&Config::default(), TranscriptionBackend::ElevenLabs).unwrap();
+        assert!(transcriber.needs_chunking());
+    }
+
+    #[test]
+    fn parse_retry_after_reads_seconds_and_dates() {
+        assert_eq!(parse_retry_after("5"), Some(Duration::from_secs(5)));
+        assert_eq!(parse_retry_after(" 7 "), Some(Duration::from_secs(7)));
+        assert_eq!(parse_retry_after("soon"), None);
+
+        let later = (chrono::Utc::now() + chrono::Duration::seconds(30)).to_rfc2822();
+        let delay = parse_retry_after(&later).unwrap();
+        assert!(
+            delay > Duration::from_secs(25) && delay <= Duration::from_secs(30),
+            "{:?}",
+            delay
+        );
+
+        // A date in the past means there is nothing to wait for
+        assert_eq!(parse_retry_after("Tue, 01 Jan 2002 00:00:00 +0000"), None);
+    }
+
+    #[test]
+    fn error_message_finds_provider_messages() {
+        assert_eq!(
+            error_message(r#"{"detail": {"status": "invalid_model", "message": "Unknown model"}}"#),
+            "Unknown model"
+        );
+        assert_eq!(
+            error_message(r#"{"detail": "Unsupported language"}"#),
+            "Unsupported language"
+        );
+        assert_eq!(
+            error_message(
+                r#"{"error": {"message": "Invalid file format", "type": "invalid_request_error"}}"#
+            ),
+            "Invalid file format"
+        );
+        assert_eq!(
+            error_message(r#"{"error": "model not loaded"}"#),
+            "model not loaded"
+        );
+        assert_eq!(error_message(r#"{"message": "bad request"}"#), "bad request");
+        assert_eq!(error_message("  plain text body\n"), "plain text body");
+    }
+
+    #[test]
+    fn error_message_truncates_long_bodies() {
+        let message = error_message(&"é".repeat(500));
+        assert_eq!(message.chars().count(), MAX_ERROR_BODY + 3);
+        assert!(message.ends_with("..."));
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_the_cap_with_jitter() {
+        for attempt in 1..=8 {
+            let max = BASE_BACKOFF.saturating_mul(1 << (attempt - 1)).min(MAX_BACKOFF);
+            let delay = backoff(attempt);
+            assert!(
+                delay >= max / 2 && delay <= max,
+                "attempt {}: {:?}",
+                attempt,
+                delay
+            );
+        }
+    }
+
+    // Answers each connection with the next canned (status, extra headers, body) response,
+    // the last one repeating, and counts the requests
+    async fn mock_http(responses: Vec<(u16, &'static str, &'static str)>) -> (String, Arc<AtomicUsize>) {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/v1", listener.local_addr().unwrap());
+        let requests = Arc::new(AtomicUsize::new(0));
+
+        let count = Arc::clone(&requests);
+        tokio::spawn(async move {
+            loop {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                read_request(&mut socket).await;
+                let n = count.fetch_add(1, Ordering::SeqCst);
+                let (status, headers, body) = responses[n.min(responses.len() - 1)];
+                let response = format!(
+                    "HTTP/1.1 {} Mock\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n{}\r\n{}",
+                    status,
+                    body.len(),
+                    headers,
+                    body
+                );
+                socket.write_all(response.as_bytes()).await.unwrap();
+                socket.shutdown().await.unwrap();
+            }
+        });
+
+        (url, requests)
+    }
+
+    // Read headers and a Content-Length body, so the client never sees a reset mid-upload
+    async fn read_request(socket: &mut tokio::net::TcpStream) {
+        let mut data = Vec::new();
+        let mut buf = [0u8; 8192];
+        loop {
+            let n = socket.read(&mut buf).await.unwrap();
+            if n == 0 {
+                return;
+            }
+            data.extend_from_slice(&buf[..n]);
+            let Some(header_end) = data.windows(4).position(|w| w == b"\r\n\r\n") else {
+                continue;
+            };
+            let headers = String::from_utf8_lossy(&data[..header_end]).to_lowercase();
+            let length: usize = headers
+                .lines()
+                .find_map(|line| line.strip_prefix("content-length:"))
+                .and_then(|value| value.trim().parse().ok())
+                .unwrap_or(0);
+            if data.len() >= header_end + 4 + length {
+                return;
+            }
+        }
+    }
+
+    async fn transcribe_against(url: String) -> Result<Transcript> {
+        let config = Config {
+            elevenlabs_base_url: url,
+            elevenlabs_api_key: "test-key".to_string(),
+            ..Default::default()
+        };
+        let transcriber = create_transcriber(&config, TranscriptionBackend::ElevenLabs).unwrap();
+        let clip = AudioClip {
+            samples: vec![0.1; 1600],
+            sample_rate: 16_000,
+        };
+        transcriber.transcribe(&clip).await
+    }
+
+    #[tokio::test]
+    async fn unauthorized_is_not_retried() {
+        let (url, requests) =
+            mock_http(vec![(401, "", r#"{"detail": {"message": "Invalid API key"}}"#)]).await;
+
+        let e = transcribe_against(url).await.unwrap_err();
+        assert!(
+            matches!(
+                e.downcast_ref::<TranscribeError>(),
+                Some(TranscribeError::Unauthorized(401))
+            ),
+            "{:#}",
+            e
+        );
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn rate_limit_waits_for_retry_after() {
+        let (url, requests) = mock_http(vec![
+            (429, "Retry-After: 1\r\n", r#"{"detail": "slow down"}"#),
+            (200, "", r#"{"text": "hello"}"#),
+        ])
+        .await;
+
+        let start = std::time::Instant::now();
+        let transcript = transcribe_against(url).await.unwrap();
+        assert_eq!(transcript.text, "hello");
+        assert_eq!(requests.load(Ordering::SeqCst), 2);
+        assert!(
+            start.elapsed() >= Duration::from_secs(1),
+            "retried after {:?}",
+            start.elapsed()
+        );
+    }
+
+    #[tokio::test]
+    async fn server_errors_are_retried_until_max_attempts() {
+        let (url, requests) = mock_http(vec![(503, "", "upstream unavailable")]).await;
+
+        let e = transcribe_against(url).await.unwrap_err();
+        assert!(
+            matches!(
+                e.downcast_ref::<TranscribeError>(),
+                Some(TranscribeError::Server(503))
+            ),
+            "{:#}",
+            e
+        );
+        assert_eq!(requests.load(Ordering::SeqCst), MAX_ATTEMPTS as usize);
+    }
+
+    #[tokio::test]
+    async fn payload_too_large_is_not_retried() {
+        let (url, requests) = mock_http(vec![(413, "", "")]).await;
+
+        let e = transcribe_against(url).await.unwrap_err();
+        assert!(
+            matches!(
+                e.downcast_ref::<TranscribeError>(),
+                Some(TranscribeError::PayloadTooLarge)
+            ),
+            "{:#}",
+            e
+        );
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
     }
 }
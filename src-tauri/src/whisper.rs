@@ -0,0 +1,201 @@
+use crate::audio::AudioClip;
+use crate::transcribe::{Transcriber, Transcript, Word};
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use whisper_rs::{FullParams, SamplingStrategy, WhisperContext, WhisperContextParameters, WhisperSegment};
+
+// Whisper models expect 16 kHz mono input
+const WHISPER_SAMPLE_RATE: u32 = 16_000;
+
+// Loading a GGML model takes seconds, so keep the last one around between recordings
+type CachedModel = (PathBuf, Arc<WhisperContext>);
+static MODEL_CACHE: OnceLock<Mutex<Option<CachedModel>>> = OnceLock::new();
+
+fn load_model(path: &Path) -> Result<Arc<WhisperContext>> {
+    let cache = MODEL_CACHE.get_or_init(|| Mutex::new(None));
+    let mut cache = cache.lock().unwrap();
+
+    if let Some((cached_path, ctx)) = cache.as_ref() {
+        if cached_path == path {
+            return Ok(Arc::clone(ctx));
+        }
+    }
+
+    if !path.exists() {
+        anyhow::bail!("whisper model not found: {}", path.display());
+    }
+
+    whisper_rs::install_logging_hooks();
+
+    let start = std::time::Instant::now();
+    let mut params = WhisperContextParameters::default();
+    params.use_gpu(false);
+    let ctx = WhisperContext::new_with_params(path, params)
+        .map_err(|e| anyhow::anyhow!("failed to load whisper model: {}", e))?;
+    tracing::info!("loaded whisper model {} in {:?}", path.display(), start.elapsed());
+
+    let ctx = Arc::new(ctx);
+    *cache = Some((path.to_path_buf(), Arc::clone(&ctx)));
+    Ok(ctx)
+}
+
+pub struct LocalWhisper {
+    ctx: Arc<WhisperContext>,
+    language: String,
+}
+
+impl LocalWhisper {
+    pub fn new(model_path: &str, language: String) -> Result<Self> {
+        if model_path.is_empty() {
+            anyhow::bail!("no whisper model path configured");
+        }
+
+        let ctx = load_model(Path::new(model_path))?;
+
+        Ok(Self { ctx, language })
+    }
+}
+
+#[async_trait]
+impl Transcriber for LocalWhisper {
+    fn name(&self) -> &'static str {
+        "Whisper (local)"
+    }
+
+    // No timeouts or size limits, and parallel inference would just fight over the CPU
+    fn needs_chunking(&self) -> bool {
+        false
+    }
+
+    async fn transcribe(&self, clip: &AudioClip) -> Result<Transcript> {
+        if clip.is_empty() {
+            return Ok(Transcript::default());
+        }
+
+        // No upload, so skip encoding and feed the samples directly
+        let samples = clip.clone().resample(WHISPER_SAMPLE_RATE)?.samples;
+        tracing::info!(
+            "running local whisper on {:.1}s of audio (language: {})",
+            samples.len() as f32 / WHISPER_SAMPLE_RATE as f32,
+            self.language
+        );
+
+        let ctx = Arc::clone(&self.ctx);
+        let language = self.language.clone();
+        let start = std::time::Instant::now();
+
+        // Dropping this future (e.g. losing a hedged race) doesn't stop a blocking task by itself
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let _cancel = CancelOnDrop(Arc::clone(&cancelled));
+
+        // Inference is CPU-bound, keep it off the async runtime
+        let transcript = tokio::task::spawn_blocking(move || -> Result<Transcript> {
+            let mut state = ctx
+                .create_state()
+                .map_err(|e| anyhow::anyhow!("failed to create whisper state: {}", e))?;
+
+            let mut params = FullParams::new(SamplingStrategy::Greedy { best_of: 1 });
+            let threads = std::thread::available_parallelism()
+                .map(|n| n.get().min(8))
+                .unwrap_or(4);
+            params.set_n_threads(threads as i32);
+            params.set_language(Some(&language));
+            params.set_translate(false);
+            params.set_no_context(true);
+            params.set_print_special(false);
+            params.set_print_progress(false);
+            params.set_print_realtime(false);
+            params.set_print_timestamps(false);
+            params.set_token_timestamps(true);
+            params.set_abort_callback_safe(move || cancelled.load(Ordering::Relaxed));
+
+            state
+                .full(params, &samples)
+                .map_err(|e| anyhow::anyhow!("whisper inference failed: {}", e))?;
+
+            let mut transcript = Transcript::default();
+            for segment in state.as_iter() {
+                let segment_text = segment
+                    .to_str_lossy()
+                    .map_err(|e| anyhow::anyhow!("failed to read whisper segment: {}", e))?;
+                transcript.text.push_str(&segment_text);
+                collect_words(&segment, &mut transcript.words)?;
+            }
+            transcript.text = transcript.text.trim().to_string();
+            // Detected when the language is "auto", otherwise the one we asked for
+            transcript.language_code =
+                whisper_rs::get_lang_str(state.full_lang_id_from_state()).map(str::to_string);
+
+            Ok(transcript)
+        })
+        .await
+        .context("whisper task panicked")??;
+
+        tracing::info!("transcription took {:?}", start.elapsed());
+        tracing::info!("raw transcription: {}", transcript.text);
+
+        Ok(transcript)
+    }
+}
+
+// Asks a running inference to abort once the transcription it belongs to is dropped
+struct CancelOnDrop(Arc<AtomicBool>);
+
+impl Drop for CancelOnDrop {
+    fn drop(&mut self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+}
+
+// Merge sub-word tokens into words; a token starting with a space begins a new word
+fn collect_words(segment: &WhisperSegment, words: &mut Vec<Word>) -> Result<()> {
+    // The word being assembled, with the probability of each of its tokens
+    let mut current: Option<(Word, Vec<f32>)> = None;
+
+    for i in 0..segment.n_tokens() {
+        let Some(token) = segment.get_token(i) else {
+            continue;
+        };
+        let text = token
+            .to_str_lossy()
+            .map_err(|e| anyhow::anyhow!("failed to read whisper token: {}", e))?;
+        // Special tokens such as [_BEG_] and timestamps aren't speech
+        if text.starts_with("[_") || text.starts_with("<|") {
+            continue;
+        }
+
+        // Token timestamps are in centiseconds
+        let data = token.token_data();
+        let (start, end) = (data.t0 as f32 / 100.0, data.t1 as f32 / 100.0);
+        let probability = token.token_probability();
+
+        match current.as_mut() {
+            Some((word, probabilities)) if !text.starts_with(' ') => {
+                word.text.push_str(&text);
+                word.end = end;
+                probabilities.push(probability);
+            }
+            _ => {
+                words.extend(current.take().map(finish_word));
+                let word = Word {
+                    text: text.trim_start().to_string(),
+                    start,
+                    end,
+                    confidence: None,
+                };
+                current = Some((word, vec![probability]));
+            }
+        }
+    }
+
+    words.extend(current.map(finish_word));
+    Ok(())
+}
+
+fn finish_word((mut word, probabilities): (Word, Vec<f32>)) -> Word {
+    word.confidence = Some(probabilities.iter().sum::<f32>() / probabilities.len() as f32);
+    word
+}
@@ -2,14 +2,72 @@ import { useState, useEffect, useRef } from "react";
 import { invoke } from "@tauri-apps/api/core";
 import { listen } from "@tauri-apps/api/event";
 
+type TranscriptionBackend = "elevenlabs" | "local" | "openai";
+type AudioFormat = "wav" | "flac" | "opus";
+type TimestampGranularity = "none" | "word" | "character";
+
+const backendNames: Record<TranscriptionBackend, string> = {
+	elevenlabs: "ElevenLabs",
+	local: "Local Whisper",
+	openai: "OpenAI-compatible",
+};
+
 interface Config {
+	backend: TranscriptionBackend;
+	fallback_backends: TranscriptionBackend[];
+	fallback_cooldown_secs: number;
+	hedge: boolean;
+	hedge_delay_ms: number;
 	elevenlabs_api_key: string;
+	elevenlabs_base_url: string;
+	elevenlabs_model_id: string;
+	elevenlabs_diarize: boolean;
+	elevenlabs_num_speakers: number | null;
+	elevenlabs_tag_audio_events: boolean;
+	elevenlabs_timestamps: TimestampGranularity;
+	elevenlabs_format: AudioFormat;
+	whisper_model_path: string;
+	openai_base_url: string;
+	openai_model: string;
+	openai_api_key: string;
+	openai_word_timestamps: boolean;
+	openai_format: AudioFormat;
 	anthropic_api_key: string;
 	language: string;
 	auto_enter: boolean;
 	cleanup: boolean;
 	translate: boolean;
-	device_index: number | null;
+	paragraph_pause_ms: number;
+	low_confidence_threshold: number;
+	device_name: string | null;
+	input_sample_rate: number | null;
+	input_channels: number | null;
+	input_buffer_frames: number | null;
+	channel_selection: number[];
+	preroll_ms: number;
+	target_sample_rate: number;
+	max_recording_secs: number;
+	chunk_secs: number;
+	noise_suppression: boolean;
+	noise_suppression_strength: number;
+	vad: boolean;
+	vad_threshold_db: number;
+	normalize: boolean;
+	normalize_target_db: number;
+	auto_stop: boolean;
+	auto_stop_silence_ms: number;
+	continuous: boolean;
+	utterance_silence_ms: number;
+	sound_cues: boolean;
+	cue_volume: number;
+	cue_start: boolean;
+	cue_stop: boolean;
+	cue_success: boolean;
+	cue_empty: boolean;
+	cue_error: boolean;
+	streaming: boolean;
+	streaming_url: string;
+	streaming_model: string;
 }
 
 interface AudioDevice {
@@ -17,22 +75,111 @@ interface AudioDevice {
 	name: string;
 }
 
+interface AudioLevel {
+	rms_db: number;
+	peak_db: number;
+}
+
 interface LogEntry {
 	timestamp: string;
 	level: string;
 	message: string;
 }
 
+interface Word {
+	text: string;
+	start: number;
+	end: number;
+	confidence: number | null;
+}
+
+interface Segment {
+	speaker: string;
+	text: string;
+	start: number;
+	end: number;
+}
+
+interface Transcript {
+	text: string;
+	language_code: string | null;
+	language_probability: number | null;
+	words: Word[];
+	segments: Segment[];
+}
+
+interface BackendStats {
+	backend: string;
+	requests: number;
+	successes: number;
+	races: number;
+	wins: number;
+	avg_latency_ms: number;
+}
+
+interface BuildFeatures {
+	local_whisper: boolean;
+	opus: boolean;
+}
+
 type AppStatus = "Idle" | "Recording" | "Transcribing";
 
 const defaultConfig: Config = {
+	backend: "elevenlabs",
+	fallback_backends: [],
+	fallback_cooldown_secs: 60,
+	hedge: false,
+	hedge_delay_ms: 1500,
 	elevenlabs_api_key: "",
+	elevenlabs_base_url: "https://api.elevenlabs.io/v1",
+	elevenlabs_model_id: "scribe_v1",
+	elevenlabs_diarize: false,
+	elevenlabs_num_speakers: null,
+	elevenlabs_tag_audio_events: true,
+	elevenlabs_timestamps: "word",
+	elevenlabs_format: "wav",
+	whisper_model_path: "",
+	openai_base_url: "http://localhost:8000/v1",
+	openai_model: "whisper-1",
+	openai_api_key: "",
+	openai_word_timestamps: false,
+	openai_format: "wav",
 	anthropic_api_key: "",
 	language: "auto",
 	auto_enter: true,
 	cleanup: false,
 	translate: false,
-	device_index: null,
+	paragraph_pause_ms: 0,
+	low_confidence_threshold: 0.5,
+	device_name: null,
+	input_sample_rate: null,
+	input_channels: null,
+	input_buffer_frames: null,
+	channel_selection: [],
+	preroll_ms: 300,
+	target_sample_rate: 16000,
+	max_recording_secs: 600,
+	chunk_secs: 60,
+	noise_suppression: false,
+	noise_suppression_strength: 0.7,
+	vad: true,
+	vad_threshold_db: -50,
+	normalize: true,
+	normalize_target_db: -20,
+	auto_stop: false,
+	auto_stop_silence_ms: 1500,
+	continuous: false,
+	utterance_silence_ms: 800,
+	sound_cues: false,
+	cue_volume: 0.5,
+	cue_start: true,
+	cue_stop: true,
+	cue_success: true,
+	cue_empty: true,
+	cue_error: true,
+	streaming: false,
+	streaming_url: "wss://api.elevenlabs.io/v1/speech-to-text/realtime",
+	streaming_model: "scribe_v2_realtime",
 };
 
 function App() {
@@ -40,6 +187,15 @@ function App() {
 	const [devices, setDevices] = useState<AudioDevice[]>([]);
 	const [status, setStatus] = useState<AppStatus>("Idle");
 	const [logs, setLogs] = useState<LogEntry[]>([]);
+	const [backendStats, setBackendStats] = useState<BackendStats[]>([]);
+	const [features, setFeatures] = useState<BuildFeatures>({
+		local_whisper: false,
+		opus: false,
+	});
+	const [liveText, setLiveText] = useState("");
+	const [transcript, setTranscript] = useState<Transcript | null>(null);
+	const [notice, setNotice] = useState("");
+	const [level, setLevel] = useState<AudioLevel | null>(null);
 	const [saved, setSaved] = useState(false);
 	const [showLogs, setShowLogs] = useState(false);
 	const logsEndRef = useRef<HTMLDivElement>(null);
@@ -49,11 +205,60 @@ function App() {
 		loadDevices();
 		loadStatus();
 		loadLogs();
+		loadBackendStats();
+		loadFeatures();
+
+		// Realtime transcript committed so far, reset when a recording starts
+		let committed = "";
 
 		// Listen for status changes
 		const unlisten = listen<AppStatus>("status-changed", (event) => {
 			setStatus(event.payload);
+			setLevel(null);
+			if (event.payload === "Recording") {
+				committed = "";
+				setLiveText("");
+			}
 			loadLogs(); // Refresh logs on status change
+			loadBackendStats();
+		});
+
+		// Live transcript from realtime streaming
+		const unlistenPartial = listen<string>("transcript-partial", (event) => {
+			setLiveText(`${committed} ${event.payload}`.trim());
+		});
+		const unlistenFinal = listen<string>("transcript-final", (event) => {
+			committed = `${committed} ${event.payload}`.trim();
+			setLiveText(committed);
+		});
+
+		const unlistenLevel = listen<AudioLevel>("audio-level", (event) => {
+			setLevel(event.payload);
+		});
+
+		// Microphone hot-plug notifications
+		const unlistenDeviceLost = listen<string>("audio-device-lost", (event) => {
+			setNotice(`Microphone disconnected: ${event.payload}`);
+			loadDevices();
+		});
+		const unlistenDeviceChanged = listen<string>(
+			"audio-device-changed",
+			(event) => {
+				setNotice(`Using microphone: ${event.payload}`);
+				loadDevices();
+			},
+		);
+
+		const unlistenLimit = listen<number>("recording-limit", (event) => {
+			setNotice(`Recording limit: stopping in ${event.payload}s`);
+		});
+
+		const unlistenError = listen<string>("transcription-error", (event) => {
+			setNotice(event.payload);
+		});
+
+		const unlistenTranscript = listen<Transcript>("transcript", (event) => {
+			setTranscript(event.payload);
 		});
 
 		// Poll logs every 2 seconds
@@ -61,6 +266,14 @@ function App() {
 
 		return () => {
 			unlisten.then((fn) => fn());
+			unlistenPartial.then((fn) => fn());
+			unlistenFinal.then((fn) => fn());
+			unlistenLevel.then((fn) => fn());
+			unlistenDeviceLost.then((fn) => fn());
+			unlistenDeviceChanged.then((fn) => fn());
+			unlistenLimit.then((fn) => fn());
+			unlistenError.then((fn) => fn());
+			unlistenTranscript.then((fn) => fn());
 			clearInterval(interval);
 		};
 	}, []);
@@ -106,6 +319,24 @@ function App() {
 		}
 	}
 
+	async function loadBackendStats() {
+		try {
+			const stats = await invoke<BackendStats[]>("get_backend_stats");
+			setBackendStats(stats);
+		} catch (e) {
+			console.error("Failed to load backend stats:", e);
+		}
+	}
+
+	async function loadFeatures() {
+		try {
+			const f = await invoke<BuildFeatures>("get_build_features");
+			setFeatures(f);
+		} catch (e) {
+			console.error("Failed to load build features:", e);
+		}
+	}
+
 	async function saveConfig() {
 		try {
 			await invoke("save_config", { config });
@@ -126,6 +357,10 @@ function App() {
 		Transcribing: "#2563eb",
 	};
 
+	// Map dBFS onto a 0-100% meter with a 60 dB range
+	const meterPercent = (db: number) =>
+		Math.min(100, Math.max(0, ((db + 60) / 60) * 100));
+
 	return (
 		<div style={{ padding: "20px", maxWidth: "400px", margin: "0 auto" }}>
 			<h1 style={{ fontSize: "24px", marginBottom: "20px" }}>ezwhisper</h1>
@@ -142,8 +377,394 @@ function App() {
 				>
 					Status: <strong>{status}</strong>
 				</div>
+				{status === "Recording" && level && (
+					<div
+						style={{
+							position: "relative",
+							marginTop: "8px",
+							height: "6px",
+							background: "#1a1a1a",
+							borderRadius: "3px",
+							overflow: "hidden",
+						}}
+					>
+						<div
+							style={{
+								width: `${meterPercent(level.rms_db)}%`,
+								height: "100%",
+								background: level.peak_db > -1 ? "#dc2626" : "#22c55e",
+								transition: "width 0.05s",
+							}}
+						/>
+						<div
+							style={{
+								position: "absolute",
+								top: 0,
+								left: `${meterPercent(level.peak_db)}%`,
+								width: "2px",
+								height: "100%",
+								background: "#e0e0e0",
+							}}
+						/>
+					</div>
+				)}
+				{notice && (
+					<div
+						style={{
+							marginTop: "8px",
+							fontSize: "12px",
+							color: "#888",
+							textAlign: "center",
+						}}
+					>
+						{notice}
+					</div>
+				)}
+				{config.streaming && liveText && (
+					<div
+						style={{
+							marginTop: "8px",
+							padding: "8px",
+							background: "#1a1a1a",
+							border: "1px solid #333",
+							borderRadius: "8px",
+							fontSize: "13px",
+							color: "#ccc",
+						}}
+					>
+						{liveText}
+					</div>
+				)}
+				{transcript && !(config.streaming && liveText) && (
+					<div
+						style={{
+							marginTop: "8px",
+							padding: "8px",
+							background: "#1a1a1a",
+							border: "1px solid #333",
+							borderRadius: "8px",
+							fontSize: "13px",
+							color: "#ccc",
+						}}
+					>
+						{transcript.language_code && (
+							<div style={{ fontSize: "11px", color: "#888", marginBottom: "4px" }}>
+								{transcript.language_code}
+								{transcript.language_probability !== null &&
+									` (${Math.round(transcript.language_probability * 100)}%)`}
+							</div>
+						)}
+						{transcript.segments.length > 0
+							? transcript.segments.map((segment, i) => (
+									<div key={i} style={{ marginBottom: "4px" }}>
+										<span style={{ color: "#888" }}>{segment.speaker}:</span>{" "}
+										{segment.text}
+									</div>
+								))
+							: transcript.words.length > 0
+							? transcript.words.map((word, i) => {
+									const unsure =
+										word.confidence !== null &&
+										word.confidence < config.low_confidence_threshold;
+									return (
+										<span
+											key={i}
+											title={
+												word.confidence !== null
+													? `${Math.round(word.confidence * 100)}% at ${word.start.toFixed(1)}s`
+													: `${word.start.toFixed(1)}s`
+											}
+											style={
+												unsure
+													? { background: "#5a4a1a", color: "#fde68a" }
+													: undefined
+											}
+										>
+											{word.text}{" "}
+										</span>
+									);
+								})
+							: transcript.text}
+					</div>
+				)}
 			</div>
 
+			<div style={{ marginBottom: "16px" }}>
+				<label style={{ display: "block", marginBottom: "4px" }}>
+					Transcription Backend
+				</label>
+				<select
+					value={config.backend}
+					onChange={(e) =>
+						updateConfig("backend", e.target.value as TranscriptionBackend)
+					}
+					style={{
+						width: "100%",
+						padding: "8px",
+						background: "#2a2a2a",
+						border: "1px solid #444",
+						borderRadius: "4px",
+						color: "#e0e0e0",
+					}}
+				>
+					<option value="elevenlabs">ElevenLabs Scribe</option>
+					{features.local_whisper && (
+						<option value="local">Local Whisper (offline)</option>
+					)}
+					<option value="openai">OpenAI-compatible server</option>
+				</select>
+				{config.backend === "local" && !features.local_whisper && (
+					<div style={{ marginTop: "6px", fontSize: "12px", color: "#888" }}>
+						This build has no local Whisper support; rebuild with the
+						local-whisper feature or pick another backend.
+					</div>
+				)}
+			</div>
+
+			<div style={{ marginBottom: "16px" }}>
+				<div
+					style={{
+						display: "flex",
+						alignItems: "center",
+						gap: "12px",
+						flexWrap: "wrap",
+					}}
+				>
+					Fall back to
+					{(Object.keys(backendNames) as TranscriptionBackend[])
+						.filter((backend) => backend !== config.backend)
+						.filter((backend) => backend !== "local" || features.local_whisper)
+						.map((backend) => (
+							<label
+								key={backend}
+								style={{ display: "flex", alignItems: "center", gap: "4px" }}
+							>
+								<input
+									type="checkbox"
+									checked={config.fallback_backends.includes(backend)}
+									onChange={(e) =>
+										updateConfig(
+											"fallback_backends",
+											e.target.checked
+												? [...config.fallback_backends, backend]
+												: config.fallback_backends.filter((b) => b !== backend),
+										)
+									}
+								/>
+								{backendNames[backend]}
+							</label>
+						))}
+				</div>
+				{config.fallback_backends.length > 0 && (
+					<label
+						style={{
+							display: "flex",
+							alignItems: "center",
+							gap: "8px",
+							marginTop: "6px",
+						}}
+					>
+						Skip a failing backend for
+						<input
+							type="number"
+							min={0}
+							step={30}
+							value={config.fallback_cooldown_secs}
+							onChange={(e) =>
+								updateConfig(
+									"fallback_cooldown_secs",
+									parseInt(e.target.value) || 0,
+								)
+							}
+							style={{
+								width: "70px",
+								padding: "4px",
+								background: "#2a2a2a",
+								border: "1px solid #444",
+								borderRadius: "4px",
+								color: "#e0e0e0",
+							}}
+						/>
+						s
+					</label>
+				)}
+				{config.fallback_backends.length > 0 && (
+					<label
+						style={{
+							display: "flex",
+							alignItems: "center",
+							gap: "8px",
+							marginTop: "6px",
+						}}
+					>
+						<input
+							type="checkbox"
+							checked={config.hedge}
+							onChange={(e) => updateConfig("hedge", e.target.checked)}
+						/>
+						Race the first fallback after
+						<input
+							type="number"
+							min={0}
+							step={250}
+							value={config.hedge_delay_ms}
+							disabled={!config.hedge}
+							onChange={(e) =>
+								updateConfig("hedge_delay_ms", parseInt(e.target.value) || 0)
+							}
+							style={{
+								width: "70px",
+								padding: "4px",
+								background: "#2a2a2a",
+								border: "1px solid #444",
+								borderRadius: "4px",
+								color: "#e0e0e0",
+							}}
+						/>
+						ms
+					</label>
+				)}
+				{config.hedge && backendStats.length > 0 && (
+					<div style={{ marginTop: "6px", fontSize: "12px", color: "#888" }}>
+						{backendStats.map((stats) => (
+							<div key={stats.backend}>
+								{stats.backend}: won {stats.wins}/{stats.races} races, avg{" "}
+								{stats.avg_latency_ms}ms over {stats.successes}/
+								{stats.requests} requests
+							</div>
+						))}
+					</div>
+				)}
+			</div>
+
+			{config.backend !== "local" && (
+				<div style={{ marginBottom: "16px" }}>
+					<label style={{ display: "block", marginBottom: "4px" }}>
+						Upload Format
+					</label>
+					<select
+						value={
+							config.backend === "openai"
+								? config.openai_format
+								: config.elevenlabs_format
+						}
+						onChange={(e) =>
+							updateConfig(
+								config.backend === "openai"
+									? "openai_format"
+									: "elevenlabs_format",
+								e.target.value as AudioFormat,
+							)
+						}
+						style={{
+							width: "100%",
+							padding: "8px",
+							background: "#2a2a2a",
+							border: "1px solid #444",
+							borderRadius: "4px",
+							color: "#e0e0e0",
+						}}
+					>
+						<option value="wav">WAV (uncompressed)</option>
+						<option value="flac">FLAC (lossless, ~50% smaller)</option>
+						{features.opus && (
+							<option value="opus">Opus (smallest, for slow connections)</option>
+						)}
+					</select>
+				</div>
+			)}
+
+			{(config.backend === "local" ||
+				config.fallback_backends.includes("local")) && (
+				<div style={{ marginBottom: "16px" }}>
+					<label style={{ display: "block", marginBottom: "4px" }}>
+						Whisper Model Path
+					</label>
+					<input
+						type="text"
+						value={config.whisper_model_path}
+						placeholder="/path/to/ggml-base.en.bin"
+						onChange={(e) => updateConfig("whisper_model_path", e.target.value)}
+						style={{
+							width: "100%",
+							padding: "8px",
+							background: "#2a2a2a",
+							border: "1px solid #444",
+							borderRadius: "4px",
+							color: "#e0e0e0",
+						}}
+					/>
+				</div>
+			)}
+
+			{(config.backend === "openai" ||
+				config.fallback_backends.includes("openai")) && (
+				<div style={{ marginBottom: "16px" }}>
+					<label style={{ display: "block", marginBottom: "4px" }}>
+						Server Base URL
+					</label>
+					<input
+						type="text"
+						value={config.openai_base_url}
+						placeholder="http://gpu-box.lan:8000/v1"
+						onChange={(e) => updateConfig("openai_base_url", e.target.value)}
+						style={{
+							width: "100%",
+							padding: "8px",
+							marginBottom: "8px",
+							background: "#2a2a2a",
+							border: "1px solid #444",
+							borderRadius: "4px",
+							color: "#e0e0e0",
+						}}
+					/>
+					<label style={{ display: "block", marginBottom: "4px" }}>
+						Model
+					</label>
+					<input
+						type="text"
+						value={config.openai_model}
+						onChange={(e) => updateConfig("openai_model", e.target.value)}
+						style={{
+							width: "100%",
+							padding: "8px",
+							marginBottom: "8px",
+							background: "#2a2a2a",
+							border: "1px solid #444",
+							borderRadius: "4px",
+							color: "#e0e0e0",
+						}}
+					/>
+					<label style={{ display: "block", marginBottom: "4px" }}>
+						API Key (optional)
+					</label>
+					<input
+						type="password"
+						value={config.openai_api_key}
+						onChange={(e) => updateConfig("openai_api_key", e.target.value)}
+						style={{
+							width: "100%",
+							padding: "8px",
+							marginBottom: "8px",
+							background: "#2a2a2a",
+							border: "1px solid #444",
+							borderRadius: "4px",
+							color: "#e0e0e0",
+						}}
+					/>
+					<label style={{ display: "flex", alignItems: "center", gap: "8px" }}>
+						<input
+							type="checkbox"
+							checked={config.openai_word_timestamps}
+							onChange={(e) =>
+								updateConfig("openai_word_timestamps", e.target.checked)
+							}
+						/>
+						Request word timestamps (verbose_json)
+					</label>
+				</div>
+			)}
+
 			<div style={{ marginBottom: "16px" }}>
 				<label style={{ display: "block", marginBottom: "4px" }}>
 					ElevenLabs API Key
@@ -163,6 +784,109 @@ function App() {
 				/>
 			</div>
 
+			{(config.backend === "elevenlabs" ||
+				config.fallback_backends.includes("elevenlabs")) && (
+				<div style={{ marginBottom: "16px" }}>
+					<label style={{ display: "block", marginBottom: "4px" }}>
+						Scribe Model
+					</label>
+					<input
+						type="text"
+						value={config.elevenlabs_model_id}
+						onChange={(e) => updateConfig("elevenlabs_model_id", e.target.value)}
+						style={{
+							width: "100%",
+							padding: "8px",
+							marginBottom: "8px",
+							background: "#2a2a2a",
+							border: "1px solid #444",
+							borderRadius: "4px",
+							color: "#e0e0e0",
+						}}
+					/>
+					<label
+						style={{
+							display: "flex",
+							alignItems: "center",
+							gap: "8px",
+							marginBottom: "8px",
+						}}
+					>
+						<input
+							type="checkbox"
+							checked={config.elevenlabs_diarize}
+							onChange={(e) =>
+								updateConfig("elevenlabs_diarize", e.target.checked)
+							}
+						/>
+						Label speakers
+						<input
+							type="number"
+							min={1}
+							max={32}
+							placeholder="auto"
+							value={config.elevenlabs_num_speakers ?? ""}
+							disabled={!config.elevenlabs_diarize}
+							onChange={(e) =>
+								updateConfig(
+									"elevenlabs_num_speakers",
+									e.target.value ? parseInt(e.target.value) : null,
+								)
+							}
+							style={{
+								width: "70px",
+								padding: "4px",
+								background: "#2a2a2a",
+								border: "1px solid #444",
+								borderRadius: "4px",
+								color: "#e0e0e0",
+							}}
+						/>
+						speakers
+					</label>
+					<label
+						style={{
+							display: "flex",
+							alignItems: "center",
+							gap: "8px",
+							marginBottom: "8px",
+						}}
+					>
+						<input
+							type="checkbox"
+							checked={config.elevenlabs_tag_audio_events}
+							onChange={(e) =>
+								updateConfig("elevenlabs_tag_audio_events", e.target.checked)
+							}
+						/>
+						Tag audio events like (laughter)
+					</label>
+					<label style={{ display: "flex", alignItems: "center", gap: "8px" }}>
+						Timestamps
+						<select
+							value={config.elevenlabs_timestamps}
+							onChange={(e) =>
+								updateConfig(
+									"elevenlabs_timestamps",
+									e.target.value as TimestampGranularity,
+								)
+							}
+							style={{
+								padding: "4px",
+								background: "#2a2a2a",
+								border: "1px solid #444",
+								borderRadius: "4px",
+								color: "#e0e0e0",
+							}}
+						>
+							<option value="none">None</option>
+							<option value="word">Word</option>
+							<option value="character">Character</option>
+						</select>
+					</label>
+				</div>
+			)}
+
 			<div style={{ marginBottom: "16px" }}>
 				<label style={{ display: "block", marginBottom: "4px" }}>
 					Anthropic API Key (optional)
@@ -187,12 +911,9 @@ function App() {
 					Audio Device
 				</label>
 				<select
-					value={config.device_index ?? ""}
+					value={config.device_name ?? ""}
 					onChange={(e) =>
-						updateConfig(
-							"device_index",
-							e.target.value ? parseInt(e.target.value) : null,
-						)
+						updateConfig("device_name", e.target.value || null)
 					}
 					style={{
 						width: "100%",
@@ -205,11 +926,101 @@ function App() {
 				>
 					<option value="">Default</option>
 					{devices.map((d) => (
-						<option key={d.index} value={d.index}>
+						<option key={d.index} value={d.name}>
 							{d.name}
 						</option>
 					))}
 				</select>
+				<div style={{ display: "flex", gap: "8px", marginTop: "8px" }}>
+					<select
+						value={config.input_sample_rate ?? ""}
+						onChange={(e) =>
+							updateConfig(
+								"input_sample_rate",
+								e.target.value ? parseInt(e.target.value) : null,
+							)
+						}
+						style={{
+							flex: 1,
+							padding: "6px",
+							background: "#2a2a2a",
+							border: "1px solid #444",
+							borderRadius: "4px",
+							color: "#e0e0e0",
+						}}
+					>
+						<option value="">Default rate</option>
+						<option value="16000">16 kHz</option>
+						<option value="44100">44.1 kHz</option>
+						<option value="48000">48 kHz</option>
+						<option value="96000">96 kHz</option>
+					</select>
+					<input
+						type="number"
+						min={1}
+						placeholder="Channels"
+						value={config.input_channels ?? ""}
+						onChange={(e) =>
+							updateConfig(
+								"input_channels",
+								e.target.value ? parseInt(e.target.value) : null,
+							)
+						}
+						style={{
+							width: "80px",
+							padding: "6px",
+							background: "#2a2a2a",
+							border: "1px solid #444",
+							borderRadius: "4px",
+							color: "#e0e0e0",
+						}}
+					/>
+					<input
+						type="number"
+						min={32}
+						step={32}
+						placeholder="Buffer"
+						value={config.input_buffer_frames ?? ""}
+						onChange={(e) =>
+							updateConfig(
+								"input_buffer_frames",
+								e.target.value ? parseInt(e.target.value) : null,
+							)
+						}
+						style={{
+							width: "80px",
+							padding: "6px",
+							background: "#2a2a2a",
+							border: "1px solid #444",
+							borderRadius: "4px",
+							color: "#e0e0e0",
+						}}
+					/>
+				</div>
+				<input
+					type="text"
+					placeholder="Input channels to record, e.g. 3 or 1,2 (empty = all)"
+					defaultValue={config.channel_selection.join(",")}
+					key={config.channel_selection.join(",")}
+					onBlur={(e) =>
+						updateConfig(
+							"channel_selection",
+							e.target.value
+								.split(",")
+								.map((c) => parseInt(c.trim()))
+								.filter((c) => c > 0),
+						)
+					}
+					style={{
+						width: "100%",
+						marginTop: "8px",
+						padding: "6px",
+						background: "#2a2a2a",
+						border: "1px solid #444",
+						borderRadius: "4px",
+						color: "#e0e0e0",
+					}}
+				/>
 			</div>
 
 			<div style={{ marginBottom: "16px" }}>
@@ -248,6 +1059,311 @@ function App() {
 				</label>
 			</div>
 
+			<div style={{ marginBottom: "12px" }}>
+				<label style={{ display: "flex", alignItems: "center", gap: "8px" }}>
+					<input
+						type="checkbox"
+						checked={config.sound_cues}
+						onChange={(e) => updateConfig("sound_cues", e.target.checked)}
+					/>
+					Sound cues
+					<input
+						type="range"
+						min={0}
+						max={1}
+						step={0.1}
+						value={config.cue_volume}
+						disabled={!config.sound_cues}
+						onChange={(e) =>
+							updateConfig("cue_volume", parseFloat(e.target.value))
+						}
+						style={{ flex: 1 }}
+					/>
+				</label>
+				{config.sound_cues && (
+					<div
+						style={{
+							display: "flex",
+							gap: "12px",
+							marginTop: "6px",
+							fontSize: "13px",
+							color: "#aaa",
+						}}
+					>
+						{(
+							[
+								["cue_start", "Start"],
+								["cue_stop", "Stop"],
+								["cue_success", "Success"],
+								["cue_empty", "Empty"],
+								["cue_error", "Error"],
+							] as const
+						).map(([key, label]) => (
+							<label
+								key={key}
+								style={{ display: "flex", alignItems: "center", gap: "4px" }}
+							>
+								<input
+									type="checkbox"
+									checked={config[key]}
+									onChange={(e) => updateConfig(key, e.target.checked)}
+								/>
+								{label}
+							</label>
+						))}
+					</div>
+				)}
+			</div>
+
+			<div style={{ marginBottom: "12px" }}>
+				<label style={{ display: "flex", alignItems: "center", gap: "8px" }}>
+					Max recording length
+					<input
+						type="number"
+						min={0}
+						step={60}
+						value={config.max_recording_secs}
+						onChange={(e) =>
+							updateConfig("max_recording_secs", parseInt(e.target.value) || 0)
+						}
+						style={{
+							width: "70px",
+							padding: "4px",
+							background: "#2a2a2a",
+							border: "1px solid #444",
+							borderRadius: "4px",
+							color: "#e0e0e0",
+						}}
+					/>
+					s (0 = unlimited)
+				</label>
+			</div>
+
+			<div style={{ marginBottom: "12px" }}>
+				<label style={{ display: "flex", alignItems: "center", gap: "8px" }}>
+					Split long recordings into
+					<input
+						type="number"
+						min={0}
+						step={10}
+						value={config.chunk_secs}
+						onChange={(e) =>
+							updateConfig("chunk_secs", parseInt(e.target.value) || 0)
+						}
+						style={{
+							width: "70px",
+							padding: "4px",
+							background: "#2a2a2a",
+							border: "1px solid #444",
+							borderRadius: "4px",
+							color: "#e0e0e0",
+						}}
+					/>
+					s chunks
+				</label>
+			</div>
+
+			<div style={{ marginBottom: "12px" }}>
+				<label style={{ display: "flex", alignItems: "center", gap: "8px" }}>
+					<input
+						type="checkbox"
+						checked={config.noise_suppression}
+						onChange={(e) =>
+							updateConfig("noise_suppression", e.target.checked)
+						}
+					/>
+					Suppress background noise
+					<input
+						type="range"
+						min={0}
+						max={1}
+						step={0.1}
+						value={config.noise_suppression_strength}
+						disabled={!config.noise_suppression}
+						onChange={(e) =>
+							updateConfig(
+								"noise_suppression_strength",
+								parseFloat(e.target.value),
+							)
+						}
+						style={{ flex: 1 }}
+					/>
+				</label>
+			</div>
+
+			<div style={{ marginBottom: "12px" }}>
+				<label style={{ display: "flex", alignItems: "center", gap: "8px" }}>
+					<input
+						type="checkbox"
+						checked={config.vad}
+						onChange={(e) => updateConfig("vad", e.target.checked)}
+					/>
+					Trim silence and skip recordings without speech
+				</label>
+			</div>
+
+			<div style={{ marginBottom: "12px" }}>
+				<label style={{ display: "flex", alignItems: "center", gap: "8px" }}>
+					<input
+						type="checkbox"
+						checked={config.normalize}
+						onChange={(e) => updateConfig("normalize", e.target.checked)}
+					/>
+					Normalize volume to
+					<input
+						type="number"
+						max={-3}
+						step={1}
+						value={config.normalize_target_db}
+						disabled={!config.normalize}
+						onChange={(e) =>
+							updateConfig(
+								"normalize_target_db",
+								parseFloat(e.target.value) || -20,
+							)
+						}
+						style={{
+							width: "60px",
+							padding: "4px",
+							background: "#2a2a2a",
+							border: "1px solid #444",
+							borderRadius: "4px",
+							color: "#e0e0e0",
+						}}
+					/>
+					dBFS and limit peaks
+				</label>
+			</div>
+
+			<div style={{ marginBottom: "12px" }}>
+				<label style={{ display: "flex", alignItems: "center", gap: "8px" }}>
+					<input
+						type="checkbox"
+						checked={config.auto_stop}
+						onChange={(e) => updateConfig("auto_stop", e.target.checked)}
+					/>
+					Hands-free: stop recording after
+					<input
+						type="number"
+						min={300}
+						step={100}
+						value={config.auto_stop_silence_ms}
+						disabled={!config.auto_stop}
+						onChange={(e) =>
+							updateConfig(
+								"auto_stop_silence_ms",
+								parseInt(e.target.value) || 1500,
+							)
+						}
+						style={{
+							width: "70px",
+							padding: "4px",
+							background: "#2a2a2a",
+							border: "1px solid #444",
+							borderRadius: "4px",
+							color: "#e0e0e0",
+						}}
+					/>
+					ms of silence
+				</label>
+			</div>
+
+			<div style={{ marginBottom: "12px" }}>
+				<label style={{ display: "flex", alignItems: "center", gap: "8px" }}>
+					<input
+						type="checkbox"
+						checked={config.continuous}
+						onChange={(e) => updateConfig("continuous", e.target.checked)}
+					/>
+					Continuous dictation: insert each sentence after
+					<input
+						type="number"
+						min={300}
+						step={100}
+						value={config.utterance_silence_ms}
+						disabled={!config.continuous}
+						onChange={(e) =>
+							updateConfig(
+								"utterance_silence_ms",
+								parseInt(e.target.value) || 800,
+							)
+						}
+						style={{
+							width: "70px",
+							padding: "4px",
+							background: "#2a2a2a",
+							border: "1px solid #444",
+							borderRadius: "4px",
+							color: "#e0e0e0",
+						}}
+					/>
+					ms pause
+				</label>
+			</div>
+
+			<div style={{ marginBottom: "12px" }}>
+				<label style={{ display: "flex", alignItems: "center", gap: "8px" }}>
+					<input
+						type="checkbox"
+						checked={config.streaming}
+						onChange={(e) => updateConfig("streaming", e.target.checked)}
+					/>
+					Stream audio while speaking (realtime, uses ElevenLabs key)
+				</label>
+			</div>
+
+			<div style={{ marginBottom: "12px" }}>
+				<label style={{ display: "flex", alignItems: "center", gap: "8px" }}>
+					New paragraph after a
+					<input
+						type="number"
+						min={0}
+						step={250}
+						value={config.paragraph_pause_ms}
+						onChange={(e) =>
+							updateConfig("paragraph_pause_ms", parseInt(e.target.value) || 0)
+						}
+						style={{
+							width: "70px",
+							padding: "4px",
+							background: "#2a2a2a",
+							border: "1px solid #444",
+							borderRadius: "4px",
+							color: "#e0e0e0",
+						}}
+					/>
+					ms pause (0 = off)
+				</label>
+			</div>
+
+			<div style={{ marginBottom: "12px" }}>
+				<label style={{ display: "flex", alignItems: "center", gap: "8px" }}>
+					Highlight words below
+					<input
+						type="number"
+						min={0}
+						max={1}
+						step={0.05}
+						value={config.low_confidence_threshold}
+						onChange={(e) =>
+							updateConfig(
+								"low_confidence_threshold",
+								parseFloat(e.target.value) || 0,
+							)
+						}
+						style={{
+							width: "70px",
+							padding: "4px",
+							background: "#2a2a2a",
+							border: "1px solid #444",
+							borderRadius: "4px",
+							color: "#e0e0e0",
+						}}
+					/>
+					confidence
+				</label>
+			</div>
+
 			<div style={{ marginBottom: "12px" }}>
 				<label style={{ display: "flex", alignItems: "center", gap: "8px" }}>
 					<input